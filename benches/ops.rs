@@ -0,0 +1,156 @@
+//! exp, log, adjoint, composition, inverse and action of SO3 and SE3 in f32 and f64
+//!
+//! every operation is measured at a generic angle, and near the singularities of the maps,
+//! a small angle where the Taylor expansions are used, and an angle close to π.
+//! the `se3_homogeneous` groups measure the operations on the 4x4 matrix SE3 was stored as
+//! before 0.5, to compare with the rotation and translation blocks.
+//!
+//! run with `cargo bench --bench ops`
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use liealg::{hat, prelude::*, Point, Vec3, Vec6};
+use nalgebra::{Matrix3, Matrix4, Matrix6, Vector3, Vector4};
+
+/// rotation angles of the benchmarked inputs
+const ANGLES: [(&str, f64); 3] = [
+    ("generic", 1.),
+    ("small", 1e-6),
+    ("near_pi", core::f64::consts::PI - 1e-4),
+];
+
+fn cast<T: Real>(x: f64) -> T {
+    T::from(x).unwrap()
+}
+
+fn rotation<T: Real>(theta: f64) -> Vec3<T> {
+    let n = 14f64.sqrt();
+    Vec3::new(cast(theta / n), cast(-2. * theta / n), cast(3. * theta / n))
+}
+
+fn twist<T: Real>(theta: f64) -> Vec6<T> {
+    let n = 14f64.sqrt();
+    Vec6::new(
+        [cast(theta / n), cast(-2. * theta / n), cast(3. * theta / n)],
+        [cast(1.), cast(-0.5), cast(2.)],
+    )
+}
+
+/// SE3 as the homogeneous 4x4 matrix, with the operations of liealg 0.4
+struct Homogeneous<T>(Matrix4<T>);
+
+impl<T: Real> Homogeneous<T> {
+    fn rp(&self) -> (Matrix3<T>, Vector3<T>) {
+        let r = self.0.fixed_view::<3, 3>(0, 0);
+        let p = self.0.fixed_view::<3, 1>(0, 3);
+        (r.into(), p.into())
+    }
+
+    fn from_rp(r: &Matrix3<T>, p: &Vector3<T>) -> Self {
+        let mut val = Matrix4::identity();
+        val.fixed_view_mut::<3, 3>(0, 0).copy_from(r);
+        val.fixed_view_mut::<3, 1>(0, 3).copy_from(p);
+        Self(val)
+    }
+
+    fn adjoint(&self) -> Matrix6<T> {
+        let (r, p) = self.rp();
+        let p_so3 = hat(&p);
+        let mut res = Matrix6::zeros();
+        res.view_mut((0, 0), (3, 3)).copy_from(&r);
+        res.view_mut((3, 0), (3, 3)).copy_from(&(p_so3 * r));
+        res.view_mut((3, 3), (3, 3)).copy_from(&r);
+        res
+    }
+
+    fn inv(&self) -> Self {
+        let (r, p) = self.rp();
+        Self::from_rp(&r.transpose(), &(-r.transpose() * p))
+    }
+
+    fn mat_mul(&self, other: &Self) -> Self {
+        Self(self.0 * other.0)
+    }
+
+    fn act(&self, x: &Vector3<T>) -> Vector3<T> {
+        let p4 = self.0 * Vector4::new(x.x, x.y, x.z, T::one());
+        Vector3::new(p4.x, p4.y, p4.z)
+    }
+}
+
+fn bench_type<T: Real>(c: &mut Criterion, ty: &str) {
+    let point = Point::new(cast::<T>(0.1), cast(-0.2), cast(2.));
+
+    let mut group = c.benchmark_group(format!("so3/{ty}"));
+    for (case, theta) in ANGLES {
+        let tau = rotation::<T>(theta).hat();
+        let g = tau.exp();
+        let h = rotation::<T>(0.5).hat().exp();
+        group.bench_function(BenchmarkId::new("exp", case), |b| {
+            b.iter(|| black_box(&tau).exp())
+        });
+        group.bench_function(BenchmarkId::new("log", case), |b| {
+            b.iter(|| black_box(&g).log())
+        });
+        group.bench_function(BenchmarkId::new("adjoint", case), |b| {
+            b.iter(|| black_box(&g).adjoint())
+        });
+        group.bench_function(BenchmarkId::new("mat_mul", case), |b| {
+            b.iter(|| black_box(&g).mat_mul(black_box(&h)))
+        });
+        group.bench_function(BenchmarkId::new("inv", case), |b| {
+            b.iter(|| black_box(&g).inv())
+        });
+        group.bench_function(BenchmarkId::new("act", case), |b| {
+            b.iter(|| black_box(&g).act(black_box(&point)))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("se3/{ty}"));
+    for (case, theta) in ANGLES {
+        let xi = twist::<T>(theta).hat();
+        let g = xi.exp();
+        let h = twist::<T>(0.5).hat().exp();
+        group.bench_function(BenchmarkId::new("exp", case), |b| {
+            b.iter(|| black_box(&xi).exp())
+        });
+        group.bench_function(BenchmarkId::new("log", case), |b| {
+            b.iter(|| black_box(&g).log())
+        });
+        group.bench_function(BenchmarkId::new("adjoint", case), |b| {
+            b.iter(|| black_box(&g).adjoint())
+        });
+        group.bench_function(BenchmarkId::new("mat_mul", case), |b| {
+            b.iter(|| black_box(&g).mat_mul(black_box(&h)))
+        });
+        group.bench_function(BenchmarkId::new("inv", case), |b| {
+            b.iter(|| black_box(&g).inv())
+        });
+        group.bench_function(BenchmarkId::new("act", case), |b| {
+            b.iter(|| black_box(&g).act(black_box(&point)))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("se3_homogeneous/{ty}"));
+    let g = Homogeneous(twist::<T>(1.).hat().exp().matrix());
+    let h = Homogeneous(twist::<T>(0.5).hat().exp().matrix());
+    let x = Vector3::new(cast::<T>(0.1), cast(-0.2), cast(2.));
+    group.bench_function("adjoint", |b| b.iter(|| black_box(&g).adjoint()));
+    group.bench_function("mat_mul", |b| {
+        b.iter(|| black_box(&g).mat_mul(black_box(&h)))
+    });
+    group.bench_function("inv", |b| b.iter(|| black_box(&g).inv()));
+    group.bench_function("act", |b| b.iter(|| black_box(&g).act(black_box(&x))));
+    group.finish();
+}
+
+fn ops(c: &mut Criterion) {
+    bench_type::<f32>(c, "f32");
+    bench_type::<f64>(c, "f64");
+}
+
+criterion_group!(benches, ops);
+criterion_main!(benches);
@@ -0,0 +1,235 @@
+//! Batch operations over slices of points, coordinate buffers and tangent vectors
+//!
+//! the rotation and translation blocks are extracted once per call,
+//! and every point is transformed by the same straight-line `R x + p`, which the compiler can vectorize.
+//!
+//! ```rust
+//! use liealg::prelude::*;
+//! use liealg::{batch, Vec6, SE3, SO3};
+//!
+//! let pose = SE3::new(&SO3::from_euler_angles(0., 0., 1.), [1., 2., 3.]);
+//! let mut cloud = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+//! pose.act_arrays(&mut cloud);
+//! assert_eq!(cloud[0], [1., 2., 3.]);
+//!
+//! let twists = [
+//!     Vec6::new([0., 0., 0.1], [1., 0., 0.]),
+//!     Vec6::new([0.2, 0., 0.], [0., 1., 0.]),
+//! ];
+//! let mut poses = [SE3::identity(), SE3::identity()];
+//! batch::exp_into(&twists, &mut poses);
+//! let mut logs = twists.clone();
+//! batch::log_into(&poses, &mut logs);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use nalgebra::{storage::StorageMut, Dim, Matrix, Matrix3, Vector3, U3};
+
+use crate::{Algebra, Group, Point, Real, Vector, SE3, SO3};
+
+/// `R x + p` on one coordinate triple
+#[inline(always)]
+fn affine<T: Real>(r: &Matrix3<T>, p: &Vector3<T>, x: [T; 3]) -> [T; 3] {
+    (r * Vector3::from(x) + p).into()
+}
+
+macro_rules! impl_batch_act {
+    ($t:ident, $blocks:expr) => {
+        impl<T: Real> $t<T> {
+            /// transform every point
+            #[cfg(feature = "alloc")]
+            pub fn act_slice(&self, points: &[Point<T>]) -> Vec<Point<T>> {
+                let (r, p) = $blocks(self);
+                points
+                    .iter()
+                    .map(|x| Point { val: r * x.val + p })
+                    .collect()
+            }
+
+            /// transform every point of `points` into `out`
+            ///
+            /// # Panics
+            /// if `points` and `out` have different lengths
+            pub fn act_into(&self, points: &[Point<T>], out: &mut [Point<T>]) {
+                assert_eq!(points.len(), out.len(), "slices of different lengths");
+                let (r, p) = $blocks(self);
+                for (x, y) in points.iter().zip(out) {
+                    y.val = r * x.val + p;
+                }
+            }
+
+            /// transform every point in place
+            pub fn act_in_place(&self, points: &mut [Point<T>]) {
+                let (r, p) = $blocks(self);
+                for x in points {
+                    x.val = r * x.val + p;
+                }
+            }
+
+            /// transform every `[x, y, z]` coordinate triple in place
+            pub fn act_arrays(&self, points: &mut [[T; 3]]) {
+                let (r, p) = $blocks(self);
+                for x in points {
+                    *x = affine(&r, &p, *x);
+                }
+            }
+
+            /// transform every column of a 3xN matrix in place, such as `Matrix3xX`
+            pub fn act_matrix<C, S>(&self, points: &mut Matrix<T, U3, C, S>)
+            where
+                C: Dim,
+                S: StorageMut<T, U3, C>,
+            {
+                let (r, p) = $blocks(self);
+                for mut x in points.column_iter_mut() {
+                    let y = r * &x + p;
+                    x.copy_from(&y);
+                }
+            }
+        }
+    };
+}
+
+impl_batch_act!(SE3, |g: &SE3<T>| g.rp());
+impl_batch_act!(SO3, |g: &SO3<T>| (g.val, Vector3::zeros()));
+
+/// exponential map of every tangent vector into `out`
+///
+/// # Panics
+/// if `tangents` and `out` have different lengths
+pub fn exp_into<V>(tangents: &[V], out: &mut [<V::Algebra as Algebra>::Group])
+where
+    V: Vector,
+    V::Algebra: Algebra,
+{
+    assert_eq!(tangents.len(), out.len(), "slices of different lengths");
+    for (tau, g) in tangents.iter().zip(out) {
+        *g = tau.hat().exp();
+    }
+}
+
+/// logarithmic map of every group element into `out`
+///
+/// # Panics
+/// if `elements` and `out` have different lengths
+pub fn log_into<G: Group>(elements: &[G], out: &mut [<G::Algebra as Algebra>::Vector]) {
+    assert_eq!(elements.len(), out.len(), "slices of different lengths");
+    for (g, tau) in elements.iter().zip(out) {
+        *tau = g.log().vee();
+    }
+}
+
+/// exponential map of every tangent vector
+#[cfg(feature = "alloc")]
+pub fn exp_slice<V>(tangents: &[V]) -> Vec<<V::Algebra as Algebra>::Group>
+where
+    V: Vector,
+    V::Algebra: Algebra,
+{
+    tangents.iter().map(|tau| tau.hat().exp()).collect()
+}
+
+/// logarithmic map of every group element
+#[cfg(feature = "alloc")]
+pub fn log_slice<G: Group>(elements: &[G]) -> Vec<<G::Algebra as Algebra>::Vector> {
+    elements.iter().map(|g| g.log().vee()).collect()
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::Matrix3xX;
+
+    use super::*;
+    use crate::{Vec3, Vec6};
+
+    fn pose() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(0.3, -0.2, 1.), [1., 2., 3.])
+    }
+
+    fn cloud() -> Vec<Point<f64>> {
+        (0..37)
+            .map(|i| {
+                let t = i as f64;
+                Point::new(t.sin(), 0.5 * t, -t.cos())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_act() {
+        let pose = pose();
+        let rot = pose.rot_trans().0;
+        let cloud = cloud();
+        let expected: Vec<_> = cloud.iter().map(|x| pose.act(x)).collect();
+        let expected_rot: Vec<_> = cloud.iter().map(|x| rot.act(x)).collect();
+
+        assert_eq!(pose.act_slice(&cloud), expected);
+        assert_eq!(rot.act_slice(&cloud), expected_rot);
+
+        let mut out = vec![Point::new(0., 0., 0.); cloud.len()];
+        pose.act_into(&cloud, &mut out);
+        assert_eq!(out, expected);
+
+        let mut in_place = cloud.clone();
+        pose.act_in_place(&mut in_place);
+        assert_eq!(in_place, expected);
+
+        let mut arrays: Vec<[f64; 3]> = cloud.iter().map(|x| x.val.into()).collect();
+        pose.act_arrays(&mut arrays);
+        for (a, b) in arrays.iter().zip(&expected) {
+            assert_relative_eq!(Vector3::from(*a), b.val, epsilon = 1e-12);
+        }
+
+        let mut matrix = Matrix3xX::from_columns(&cloud.iter().map(|x| x.val).collect::<Vec<_>>());
+        rot.act_matrix(&mut matrix);
+        for (a, b) in matrix.column_iter().zip(&expected_rot) {
+            assert_relative_eq!(Vector3::from(a), b.val, epsilon = 1e-12);
+        }
+        // fixed size matrices too
+        let mut fixed = Matrix3::identity();
+        pose.act_matrix(&mut fixed);
+        assert_relative_eq!(
+            Vector3::from(fixed.column(0)),
+            pose.act(&Point::new(1., 0., 0.)).val
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_act_into_lengths() {
+        let mut out = vec![Point::new(0., 0., 0.); 2];
+        pose().act_into(&cloud(), &mut out);
+    }
+
+    #[test]
+    fn test_exp_log() {
+        let twists: Vec<_> = (0..10)
+            .map(|i| {
+                let t = i as f64 * 0.3;
+                Vec6::new([0.1 * t, -0.2, t], [1., t, -0.5])
+            })
+            .collect();
+        let poses = exp_slice(&twists);
+        assert_eq!(poses.len(), twists.len());
+        let mut out = vec![SE3::identity(); twists.len()];
+        exp_into(&twists, &mut out);
+        for ((tau, a), b) in twists.iter().zip(&poses).zip(&out) {
+            assert_eq!(a, &tau.hat().exp());
+            assert_eq!(a, b);
+        }
+
+        let logs = log_slice(&poses);
+        let mut out = vec![Vec6::new([0.; 3], [0.; 3]); poses.len()];
+        log_into(&poses, &mut out);
+        assert_eq!(logs, out);
+        for (a, b) in logs.iter().zip(&twists) {
+            assert_relative_eq!(a, b, epsilon = 1e-12);
+        }
+
+        let rotations = exp_slice(&[Vec3::new(0., 0., 1.), Vec3::new(0.5, 0., 0.)]);
+        assert_relative_eq!(log_slice(&rotations)[1], Vec3::new(0.5, 0., 0.));
+    }
+}
@@ -0,0 +1,258 @@
+//! Random sampling of groups, tangent vectors and points, requires the `rand` feature
+//!
+//! ```rust
+//! use liealg::distributions::{Gaussian, Region};
+//! use liealg::prelude::*;
+//! use liealg::{Vec3, SE3, SO3};
+//! use nalgebra::Matrix3;
+//! use rand::Rng;
+//!
+//! let mut rng = rand::thread_rng();
+//! // Haar-uniform rotation
+//! let rot: SO3<f64> = rng.gen();
+//! // uniform rotation, translation uniform in a ball of radius 2
+//! let pose: SE3<f64> = rng.sample(Region::Ball { radius: 2. });
+//! // perturb the rotation with gaussian noise in its tangent space
+//! let noise = Gaussian::new([0.; 3], Matrix3::identity() * 0.01).unwrap();
+//! let tau: Vec3<f64> = rng.sample(&noise);
+//! let rot_ = rot.plus(&tau);
+//! ```
+
+use core::f64::consts::TAU;
+
+use nalgebra::{SMatrix, SVector, Vector3};
+use rand::{distributions::Distribution, distributions::Standard, Rng};
+
+use crate::{rigid::SE3, rot::SO3, utils::cholesky, Point, Real, Vec3, Vec6};
+
+fn uniform<T: Real, R: Rng + ?Sized>(rng: &mut R) -> T {
+    T::from(rng.gen::<f64>()).unwrap()
+}
+
+/// standard normal sample by the Box-Muller transform
+fn normal<T: Real, R: Rng + ?Sized>(rng: &mut R) -> T {
+    // 1 - u lies in (0, 1], keeping ln finite
+    let u1 = T::from(1. - rng.gen::<f64>()).unwrap();
+    let u2: T = uniform(rng);
+    let two = T::one() + T::one();
+    (-two * u1.ln()).sqrt() * (two * T::PI() * u2).cos()
+}
+
+/// Haar-uniform rotation, by Shoemake's uniform sampling of unit quaternions
+impl<T: Real> Distribution<SO3<T>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SO3<T> {
+        let u1: f64 = rng.gen();
+        let u2: f64 = rng.gen();
+        let u3: f64 = rng.gen();
+        let (a, b) = ((1. - u1).sqrt(), u1.sqrt());
+        let q = [
+            a * (TAU * u2).sin(),
+            a * (TAU * u2).cos(),
+            b * (TAU * u3).sin(),
+            b * (TAU * u3).cos(),
+        ];
+        SO3::from_quaternion(q.map(|x| T::from(x).unwrap()))
+    }
+}
+
+/// region centered at the origin, sampled uniformly
+///
+/// samples [Point]s in the region, and [SE3]s with a Haar-uniform rotation and a translation in the region
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region<T> {
+    /// axis aligned box `[-x, x] × [-y, y] × [-z, z]`
+    Box {
+        /// half of the side lengths
+        half_extents: [T; 3],
+    },
+    /// ball of the given radius
+    Ball {
+        /// radius of the ball
+        radius: T,
+    },
+}
+
+impl<T: Real> Distribution<Point<T>> for Region<T> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Point<T> {
+        let two = T::one() + T::one();
+        let mut cube = || Vector3::from_fn(|_, _| two * uniform::<T, R>(rng) - T::one());
+        let val = match *self {
+            Region::Box { half_extents } => cube().component_mul(&Vector3::from(half_extents)),
+            // rejection sampling from the enclosing cube, accepts with probability π/6
+            Region::Ball { radius } => loop {
+                let v = cube();
+                if v.dot(&v) <= T::one() {
+                    break v * radius;
+                }
+            },
+        };
+        Point { val }
+    }
+}
+
+impl<T: Real> Distribution<SE3<T>> for Region<T> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SE3<T> {
+        let rot: SO3<T> = rng.gen();
+        let p: Point<T> = self.sample(rng);
+        SE3::from_rp(&rot.val, &p.val)
+    }
+}
+
+/// gaussian distribution of `N` dimensional tangent vectors
+///
+/// samples [Vec3] with `N = 3` and [Vec6] with `N = 6`, with the `[ω; v]` ordering of [Vec6]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gaussian<T, const N: usize> {
+    mean: SVector<T, N>,
+    // lower triangular cholesky factor of the covariance
+    chol: SMatrix<T, N, N>,
+}
+
+impl<T: Real, const N: usize> Gaussian<T, N> {
+    /// gaussian with the given mean and covariance,
+    /// returns `None` if the covariance is not symmetric positive definite
+    pub fn new(mean: [T; N], cov: SMatrix<T, N, N>) -> Option<Self> {
+        let tol = T::epsilon().sqrt() * cov.iter().fold(T::one(), |m, x| m.max(x.abs()));
+        if (0..N).any(|i| (0..i).any(|j| (cov[(i, j)] - cov[(j, i)]).abs() > tol)) {
+            return None;
+        }
+        Some(Self {
+            mean: SVector::from(mean),
+            chol: cholesky(&cov)?,
+        })
+    }
+
+    /// zero mean gaussian with covariance `σ² I`
+    pub fn isotropic(sigma: T) -> Self {
+        Self {
+            mean: SVector::zeros(),
+            chol: SMatrix::identity() * sigma.abs(),
+        }
+    }
+
+    fn sample_val<R: Rng + ?Sized>(&self, rng: &mut R) -> SVector<T, N> {
+        let z = SVector::from_fn(|_, _| normal::<T, R>(rng));
+        self.mean + self.chol * z
+    }
+}
+
+impl<T: Real> Distribution<Vec3<T>> for Gaussian<T, 3> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3<T> {
+        Vec3 {
+            val: self.sample_val(rng),
+        }
+    }
+}
+
+impl<T: Real> Distribution<Vec6<T>> for Gaussian<T, 6> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec6<T> {
+        Vec6 {
+            val: self.sample_val(rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::{Matrix3, Matrix6, Vector6};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const SAMPLES: usize = 20000;
+
+    #[test]
+    fn test_haar_so3() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // E[R] = 0 for the Haar measure, and the angle density is (1 - cos θ) / π
+        let mut mean = Matrix3::zeros();
+        let mut angle = 0.;
+        for _ in 0..SAMPLES {
+            let rot: SO3<f64> = rng.gen();
+            assert_relative_eq!(
+                rot.val * rot.val.transpose(),
+                Matrix3::identity(),
+                epsilon = 1e-12
+            );
+            assert_relative_eq!(rot.val.determinant(), 1., epsilon = 1e-12);
+            mean += rot.val / SAMPLES as f64;
+            angle += rot.val.trace().mul_add(0.5, -0.5).clamp(-1., 1.).acos() / SAMPLES as f64;
+        }
+        assert_relative_eq!(mean, Matrix3::zeros(), epsilon = 0.02);
+        // E[θ] = π / 2 + 2 / π
+        assert_relative_eq!(
+            angle,
+            core::f64::consts::FRAC_PI_2 + 2. / core::f64::consts::PI,
+            epsilon = 0.02
+        );
+    }
+
+    #[test]
+    fn test_region() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let region = Region::Box {
+            half_extents: [1., 2., 3.],
+        };
+        let mut mean = Vector3::zeros();
+        for _ in 0..SAMPLES {
+            let p: Point<f64> = rng.sample(region);
+            assert!(p.x().abs() <= 1. && p.y().abs() <= 2. && p.z().abs() <= 3.);
+            mean += p.val / SAMPLES as f64;
+        }
+        assert_relative_eq!(mean, Vector3::zeros(), epsilon = 0.05);
+
+        let region = Region::Ball { radius: 2. };
+        let mut inner = 0;
+        for _ in 0..SAMPLES {
+            let pose: SE3<f64> = rng.sample(region);
+            let (rot, p) = pose.rp();
+            assert_relative_eq!(rot * rot.transpose(), Matrix3::identity(), epsilon = 1e-12);
+            assert!(p.norm() <= 2.);
+            if p.norm() <= 1. {
+                inner += 1;
+            }
+        }
+        // the inner ball of radius 1 holds 1/8 of the volume
+        assert_relative_eq!(inner as f64 / SAMPLES as f64, 0.125, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_gaussian() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let a = Matrix6::from_fn(|i, j| ((i * 7 + j * 3) % 5) as f64 * 0.1);
+        let cov = a * a.transpose() + Matrix6::identity() * 0.1;
+        let mean = [1., -2., 3., 0., 0.5, -0.5];
+        let gaussian = Gaussian::new(mean, cov).unwrap();
+        let samples: Vec<Vec6<f64>> = (&mut rng).sample_iter(&gaussian).take(SAMPLES).collect();
+        let mean_ = samples.iter().map(|v| v.val).sum::<Vector6<f64>>() / SAMPLES as f64;
+        assert_relative_eq!(mean_, Vector6::from(mean), epsilon = 0.03);
+        let cov_ = samples
+            .iter()
+            .map(|v| (v.val - mean_) * (v.val - mean_).transpose())
+            .sum::<Matrix6<f64>>()
+            / (SAMPLES - 1) as f64;
+        assert_relative_eq!(cov_, cov, epsilon = 0.03);
+
+        let gaussian = Gaussian::<f64, 3>::isotropic(0.5);
+        let var = (0..SAMPLES)
+            .map(|_| {
+                let v: Vec3<f64> = rng.sample(gaussian);
+                v.val.norm_squared()
+            })
+            .sum::<f64>()
+            / SAMPLES as f64;
+        assert_relative_eq!(var, 3. * 0.25, epsilon = 0.02);
+    }
+
+    #[test]
+    fn test_gaussian_invalid_cov() {
+        assert!(
+            Gaussian::new([0.; 3], Matrix3::new(1., 0., 0., 0., -1., 0., 0., 0., 1.)).is_none()
+        );
+        assert!(
+            Gaussian::new([0.; 3], Matrix3::new(1., 0.5, 0., 0., 1., 0., 0., 0., 1.)).is_none()
+        );
+        assert!(Gaussian::new([0.; 3], Matrix3::<f64>::zeros()).is_none());
+    }
+}
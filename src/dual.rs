@@ -0,0 +1,706 @@
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    num::FpCategory,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign},
+};
+
+use num_traits::{Float, FloatConst, Num, NumCast, One, ToPrimitive, Zero};
+
+use crate::SimdReal;
+
+/// Dual number for forward-mode automatic differentiation
+///
+/// `Dual<T, N>` carries a value and its derivatives with respect to `N` independent variables,
+/// it implements [Real](crate::Real), so every operation of liealg can be evaluated on dual numbers
+/// to get exact Jacobians.
+///
+/// comparisons only use the value, so branches are taken as they would be for the value alone.
+///
+/// ## Example
+/// ```rust
+/// use liealg::prelude::*;
+/// use liealg::{Dual, Vec3};
+/// // derivative of exp(w^) with respect to w_z at w = 0
+/// let w = Vec3::new(
+///     Dual::constant(0.),
+///     Dual::constant(0.),
+///     Dual::<f64, 1>::variable(0., 0),
+/// );
+/// let rot = w.hat().exp();
+/// let [r00, r10, ..] = rot.as_array();
+/// assert_eq!(r10.eps(), [1.]);
+/// assert_eq!(r00.eps(), [0.]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Dual<T, const N: usize = 1> {
+    pub(crate) re: T,
+    pub(crate) eps: [T; N],
+}
+
+impl<T: Float, const N: usize> Dual<T, N> {
+    /// Create a new dual number from value and derivatives
+    pub fn new(re: T, eps: [T; N]) -> Self {
+        Self { re, eps }
+    }
+
+    /// Create a constant, all derivatives are zero
+    pub fn constant(re: T) -> Self {
+        Self {
+            re,
+            eps: [T::zero(); N],
+        }
+    }
+
+    /// Create the `i`-th independent variable, its `i`-th derivative is one
+    ///
+    /// # Panics
+    /// if `i >= N`
+    pub fn variable(re: T, i: usize) -> Self {
+        let mut eps = [T::zero(); N];
+        eps[i] = T::one();
+        Self { re, eps }
+    }
+
+    /// value
+    pub fn re(&self) -> T {
+        self.re
+    }
+
+    /// derivatives
+    pub fn eps(&self) -> [T; N] {
+        self.eps
+    }
+
+    /// apply a function with value `f` and derivative `df` at `self.re`
+    ///
+    /// zero derivatives stay zero, even if `df` is not finite
+    fn chain(self, f: T, df: T) -> Self {
+        Self {
+            re: f,
+            eps: self
+                .eps
+                .map(|e| if e.is_zero() { T::zero() } else { e * df }),
+        }
+    }
+
+    fn zip(self, other: Self, f: impl Fn(T, T) -> T) -> [T; N] {
+        let mut eps = self.eps;
+        for (e, o) in eps.iter_mut().zip(other.eps) {
+            *e = f(*e, o);
+        }
+        eps
+    }
+}
+
+impl<T: Float + Display, const N: usize> Display for Dual<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.re.fmt(f)
+    }
+}
+
+impl<T: Float, const N: usize> PartialEq for Dual<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.re == other.re
+    }
+}
+
+impl<T: Float, const N: usize> PartialOrd for Dual<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.re.partial_cmp(&other.re)
+    }
+}
+
+impl<T: Float, const N: usize> Neg for Dual<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            re: -self.re,
+            eps: self.eps.map(|e| -e),
+        }
+    }
+}
+
+impl<T: Float, const N: usize> Add for Dual<T, N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re + rhs.re,
+            eps: self.zip(rhs, |a, b| a + b),
+        }
+    }
+}
+
+impl<T: Float, const N: usize> Sub for Dual<T, N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re - rhs.re,
+            eps: self.zip(rhs, |a, b| a - b),
+        }
+    }
+}
+
+impl<T: Float, const N: usize> Mul for Dual<T, N> {
+    type Output = Self;
+
+    // product rule
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re * rhs.re,
+            eps: self.zip(rhs, |a, b| a * rhs.re + self.re * b),
+        }
+    }
+}
+
+impl<T: Float, const N: usize> Div for Dual<T, N> {
+    type Output = Self;
+
+    // quotient rule
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        let re = self.re / rhs.re;
+        Self {
+            re,
+            eps: self.zip(rhs, |a, b| (a - re * b) / rhs.re),
+        }
+    }
+}
+
+impl<T: Float, const N: usize> Rem for Dual<T, N> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        let n = (self.re / rhs.re).trunc();
+        Self {
+            re: self.re % rhs.re,
+            eps: self.zip(rhs, |a, b| a - n * b),
+        }
+    }
+}
+
+macro_rules! impl_assign {
+    ($($t:ident $f:ident $op:tt)*) => {
+        $(
+            impl<T: Float, const N: usize> $t for Dual<T, N> {
+                fn $f(&mut self, rhs: Self) {
+                    *self = *self $op rhs;
+                }
+            }
+        )*
+    };
+}
+
+impl_assign!(
+    AddAssign add_assign +
+    SubAssign sub_assign -
+    MulAssign mul_assign *
+    DivAssign div_assign /
+    RemAssign rem_assign %
+);
+
+impl<T: Float, const N: usize> Zero for Dual<T, N> {
+    fn zero() -> Self {
+        Self::constant(T::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.re.is_zero()
+    }
+}
+
+impl<T: Float, const N: usize> One for Dual<T, N> {
+    fn one() -> Self {
+        Self::constant(T::one())
+    }
+}
+
+impl<T: Float, const N: usize> Num for Dual<T, N> {
+    type FromStrRadixErr = T::FromStrRadixErr;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        T::from_str_radix(str, radix).map(Self::constant)
+    }
+}
+
+impl<T: Float, const N: usize> ToPrimitive for Dual<T, N> {
+    fn to_i64(&self) -> Option<i64> {
+        self.re.to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.re.to_u64()
+    }
+
+    fn to_f32(&self) -> Option<f32> {
+        self.re.to_f32()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        self.re.to_f64()
+    }
+}
+
+impl<T: Float, const N: usize> NumCast for Dual<T, N> {
+    fn from<U: ToPrimitive>(n: U) -> Option<Self> {
+        T::from(n).map(Self::constant)
+    }
+}
+
+macro_rules! impl_float_const {
+    ($($c:ident)*) => {
+        impl<T: Float + FloatConst, const N: usize> FloatConst for Dual<T, N> {
+            $(
+                fn $c() -> Self {
+                    Self::constant(T::$c())
+                }
+            )*
+        }
+    };
+}
+
+impl_float_const!(
+    E FRAC_1_PI FRAC_1_SQRT_2 FRAC_2_PI FRAC_2_SQRT_PI FRAC_PI_2 FRAC_PI_3 FRAC_PI_4
+    FRAC_PI_6 FRAC_PI_8 LN_10 LN_2 LOG10_E LOG2_E PI SQRT_2
+);
+
+macro_rules! impl_constants {
+    ($($f:ident)*) => {
+        $(
+            fn $f() -> Self {
+                Self::constant(T::$f())
+            }
+        )*
+    };
+}
+
+macro_rules! impl_predicates {
+    ($($f:ident)*) => {
+        $(
+            fn $f(self) -> bool {
+                self.re.$f()
+            }
+        )*
+    };
+}
+
+macro_rules! impl_step {
+    ($($f:ident)*) => {
+        $(
+            fn $f(self) -> Self {
+                Self::constant(self.re.$f())
+            }
+        )*
+    };
+}
+
+impl<T: Float, const N: usize> Float for Dual<T, N> {
+    impl_constants!(
+        nan infinity neg_infinity neg_zero min_value min_positive_value epsilon max_value
+    );
+
+    impl_predicates!(
+        is_nan is_infinite is_finite is_normal is_sign_positive is_sign_negative
+    );
+
+    impl_step!(floor ceil round trunc signum);
+
+    fn classify(self) -> FpCategory {
+        self.re.classify()
+    }
+
+    fn fract(self) -> Self {
+        self.chain(self.re.fract(), T::one())
+    }
+
+    fn abs(self) -> Self {
+        if self.re.is_sign_negative() {
+            -self
+        } else {
+            self
+        }
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn recip(self) -> Self {
+        let re = self.re.recip();
+        self.chain(re, -re * re)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        // x^0 is constant, n * x^-1 would be 0 * inf at 0
+        if n == 0 {
+            return self.chain(T::one(), T::zero());
+        }
+        let df = T::from(n).unwrap() * self.re.powi(n - 1);
+        self.chain(self.re.powi(n), df)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        let re = self.re.powf(n.re);
+        let dx = n.re * self.re.powf(n.re - T::one());
+        let dn = re * self.re.ln();
+        Self {
+            re,
+            eps: self.zip(n, |a, b| {
+                let a = if a.is_zero() { T::zero() } else { a * dx };
+                let b = if b.is_zero() { T::zero() } else { b * dn };
+                a + b
+            }),
+        }
+    }
+
+    fn sqrt(self) -> Self {
+        let re = self.re.sqrt();
+        self.chain(re, (re + re).recip())
+    }
+
+    fn exp(self) -> Self {
+        let re = self.re.exp();
+        self.chain(re, re)
+    }
+
+    fn exp2(self) -> Self {
+        let re = self.re.exp2();
+        self.chain(re, re * T::from(core::f64::consts::LN_2).unwrap())
+    }
+
+    fn ln(self) -> Self {
+        self.chain(self.re.ln(), self.re.recip())
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        let ln2 = T::from(core::f64::consts::LN_2).unwrap();
+        self.chain(self.re.log2(), (self.re * ln2).recip())
+    }
+
+    fn log10(self) -> Self {
+        let ln10 = T::from(core::f64::consts::LN_10).unwrap();
+        self.chain(self.re.log10(), (self.re * ln10).recip())
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other.re > self.re || self.re.is_nan() {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other.re < self.re || self.re.is_nan() {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn abs_sub(self, other: Self) -> Self {
+        if self.re <= other.re {
+            Self::zero()
+        } else {
+            self - other
+        }
+    }
+
+    fn cbrt(self) -> Self {
+        let re = self.re.cbrt();
+        let three = T::from(3).unwrap();
+        self.chain(re, (three * re * re).recip())
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        let re = self.re.hypot(other.re);
+        Self {
+            re,
+            eps: self.zip(other, |a, b| (a * self.re + b * other.re) / re),
+        }
+    }
+
+    fn sin(self) -> Self {
+        let (sin, cos) = self.re.sin_cos();
+        self.chain(sin, cos)
+    }
+
+    fn cos(self) -> Self {
+        let (sin, cos) = self.re.sin_cos();
+        self.chain(cos, -sin)
+    }
+
+    fn tan(self) -> Self {
+        let re = self.re.tan();
+        self.chain(re, T::one() + re * re)
+    }
+
+    fn asin(self) -> Self {
+        let df = (T::one() - self.re * self.re).sqrt().recip();
+        self.chain(self.re.asin(), df)
+    }
+
+    fn acos(self) -> Self {
+        let df = -(T::one() - self.re * self.re).sqrt().recip();
+        self.chain(self.re.acos(), df)
+    }
+
+    fn atan(self) -> Self {
+        let df = (T::one() + self.re * self.re).recip();
+        self.chain(self.re.atan(), df)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        let r2 = self.re * self.re + other.re * other.re;
+        Self {
+            re: self.re.atan2(other.re),
+            eps: self.zip(other, |a, b| (a * other.re - b * self.re) / r2),
+        }
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        let (sin, cos) = self.re.sin_cos();
+        (self.chain(sin, cos), self.chain(cos, -sin))
+    }
+
+    fn exp_m1(self) -> Self {
+        self.chain(self.re.exp_m1(), self.re.exp())
+    }
+
+    fn ln_1p(self) -> Self {
+        self.chain(self.re.ln_1p(), (T::one() + self.re).recip())
+    }
+
+    fn sinh(self) -> Self {
+        self.chain(self.re.sinh(), self.re.cosh())
+    }
+
+    fn cosh(self) -> Self {
+        self.chain(self.re.cosh(), self.re.sinh())
+    }
+
+    fn tanh(self) -> Self {
+        let re = self.re.tanh();
+        self.chain(re, T::one() - re * re)
+    }
+
+    fn asinh(self) -> Self {
+        let df = (self.re * self.re + T::one()).sqrt().recip();
+        self.chain(self.re.asinh(), df)
+    }
+
+    fn acosh(self) -> Self {
+        let df = (self.re * self.re - T::one()).sqrt().recip();
+        self.chain(self.re.acosh(), df)
+    }
+
+    fn atanh(self) -> Self {
+        let df = (T::one() - self.re * self.re).recip();
+        self.chain(self.re.atanh(), df)
+    }
+
+    fn integer_decode(self) -> (u64, i16, i8) {
+        self.re.integer_decode()
+    }
+}
+
+impl<T: Float + Debug + 'static, const N: usize> SimdReal for Dual<T, N> {
+    type Mask = bool;
+
+    fn splat(x: f64) -> Self {
+        Self::constant(T::from(x).unwrap())
+    }
+
+    fn simd_epsilon() -> Self {
+        Float::epsilon()
+    }
+
+    fn simd_sqrt(self) -> Self {
+        Float::sqrt(self)
+    }
+
+    fn simd_sin_cos(self) -> (Self, Self) {
+        Float::sin_cos(self)
+    }
+
+    fn simd_atan2(self, x: Self) -> Self {
+        Float::atan2(self, x)
+    }
+
+    fn simd_lt(self, other: Self) -> bool {
+        self < other
+    }
+
+    fn simd_ge(self, other: Self) -> bool {
+        self >= other
+    }
+
+    fn select(mask: bool, if_true: Self, if_false: Self) -> Self {
+        if mask {
+            if_true
+        } else {
+            if_false
+        }
+    }
+
+    fn all(mask: bool) -> bool {
+        mask
+    }
+
+    fn none(mask: bool) -> bool {
+        !mask
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::{Matrix3, Matrix6, Vector3, Vector6};
+
+    use super::*;
+    use crate::{Algebra, Group, Point, Vec3, Vec6, Vector, SE3, SO3};
+
+    type D3 = Dual<f64, 3>;
+    type D6 = Dual<f64, 6>;
+
+    fn eps<const N: usize>(x: Dual<f64, N>) -> [f64; N] {
+        x.eps
+    }
+
+    #[test]
+    fn test_scalar() {
+        let x = Dual::<f64, 1>::variable(0.7, 0);
+        assert_relative_eq!(eps(x.sin())[0], 0.7.cos());
+        assert_relative_eq!(eps(x.acos())[0], -1. / (1. - 0.49).sqrt());
+        assert_relative_eq!(eps(x.sqrt())[0], 0.5 / 0.7.sqrt());
+        assert_relative_eq!(eps(x * x / (x + Dual::one()))[0], (0.49 + 1.4) / 1.7 / 1.7);
+        assert_relative_eq!(eps(x.atan2(Dual::constant(2.)))[0], 2. / (4. + 0.49));
+        assert_relative_eq!(eps(x.powf(Dual::constant(3.)))[0], 3. * 0.49);
+        assert_eq!(eps(Dual::<f64, 1>::constant(0.).sqrt())[0], 0.);
+        assert_relative_eq!(eps(x.powi(3))[0], 3. * 0.49);
+        let zero = Dual::<f64, 1>::variable(0., 0);
+        assert_eq!(zero.powi(0).re, 1.);
+        assert_eq!(eps(zero.powi(0))[0], 0.);
+        assert_eq!(eps(x.powi(0))[0], 0.);
+    }
+
+    /// value of a 3-vector of dual numbers and its Jacobian
+    fn split3<const N: usize>(
+        v: &Vector3<Dual<f64, N>>,
+    ) -> (Vector3<f64>, nalgebra::SMatrix<f64, 3, N>) {
+        let val = v.map(|x| x.re);
+        let jac = nalgebra::SMatrix::<f64, 3, N>::from_fn(|i, j| v[i].eps[j]);
+        (val, jac)
+    }
+
+    fn so3_dual(rot: &SO3<f64>) -> SO3<D3> {
+        SO3 {
+            val: rot.val.map(Dual::constant),
+        }
+    }
+
+    fn so3_var(v: &Vector3<f64>) -> Vec3<D3> {
+        Vec3 {
+            val: Vector3::from_fn(|i, _| Dual::variable(v[i], i)),
+        }
+    }
+
+    fn se3_var(v: &Vector6<f64>) -> Vec6<D6> {
+        Vec6 {
+            val: Vector6::from_fn(|i, _| Dual::variable(v[i], i)),
+        }
+    }
+
+    #[test]
+    fn test_so3_exp_log() {
+        for w in [
+            Vector3::new(0., 0., 0.),
+            Vector3::new(1e-9, -2e-9, 0.),
+            Vector3::new(0.3, -0.2, 0.5),
+        ] {
+            let v = Vec3 { val: w };
+            // d/dδ log(exp(w)^-1 * exp(w + δ)) = Jr
+            let r0 = so3_dual(&v.hat().exp().inv());
+            let dr = r0.mat_mul(&so3_var(&w).hat().exp());
+            let skew = (dr.val - dr.val.transpose()) * Dual::constant(0.5);
+            let jac = Matrix3::from_fn(|i, j| {
+                let k = [(2, 1), (0, 2), (1, 0)][i];
+                skew[k].eps[j]
+            });
+            assert_relative_eq!(jac, v.rjac(), epsilon = 1e-9);
+
+            // d/dδ log(exp(w) * exp(δ)) = Jr^-1
+            let rot = so3_dual(&v.hat().exp()).mat_mul(&so3_var(&Vector3::zeros()).hat().exp());
+            let (val, jac) = split3(&rot.log().val);
+            assert_relative_eq!(val, w, epsilon = 1e-12);
+            assert_relative_eq!(jac, v.rjac_inv(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_se3_exp_log() {
+        for xi in [
+            Vector6::new(0., 0., 0., 1., 2., 3.),
+            Vector6::new(1e-9, 0., -1e-9, 0., -1., 0.5),
+            Vector6::new(0.3, -0.2, 0.5, 1., 2., 3.),
+        ] {
+            let v = Vec6 { val: xi };
+            let t = v.hat().exp();
+            let (r, p) = t.rp();
+            let t0 = SE3::from_rp(&r.map(Dual::constant), &p.map(Dual::constant));
+            let d = t0
+                .mat_mul(&se3_var(&Vector6::zeros()).hat().exp())
+                .log()
+                .val;
+            let val = d.map(|x| x.re);
+            let jac = Matrix6::from_fn(|i, j| d[i].eps[j]);
+            assert_relative_eq!(val, xi, epsilon = 1e-12);
+            assert_relative_eq!(jac, v.rjac_inv(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_act_adjoint() {
+        let rot = SO3::<f64>::from_euler_angles(0.3, -0.5, 1.2);
+        let p = Point::new(1., -2., 3.);
+        let (_, j_rot, _) = rot.act_with_jacobians(&p, crate::Perturbation::Right);
+        let moved = so3_dual(&rot)
+            .mat_mul(&so3_var(&Vector3::zeros()).hat().exp())
+            .act(&Point {
+                val: p.val.map(Dual::constant),
+            });
+        let (_, jac) = split3(&moved.val);
+        assert_relative_eq!(jac, j_rot, epsilon = 1e-12);
+
+        // d/dδ Ad(T * exp(δ)) = Ad(T) * ad(e_i)
+        let t = SE3::new(&rot, [1., 2., 3.]);
+        let (r, p) = t.rp();
+        let adj = SE3::from_rp(&r.map(D6::constant), &p.map(D6::constant))
+            .mat_mul(&se3_var(&Vector6::zeros()).hat().exp())
+            .adjoint();
+        assert_relative_eq!(adj.val.map(|x| x.re), t.adjoint().val, epsilon = 1e-12);
+        for i in 0..6 {
+            let mut e = Vector6::zeros();
+            e[i] = 1.;
+            let mut ad = Matrix6::zeros();
+            let w = crate::hat(&e.fixed_rows::<3>(0).into());
+            ad.fixed_view_mut::<3, 3>(0, 0).copy_from(&w);
+            ad.fixed_view_mut::<3, 3>(3, 3).copy_from(&w);
+            ad.fixed_view_mut::<3, 3>(3, 0)
+                .copy_from(&crate::hat(&e.fixed_rows::<3>(3).into()));
+            assert_relative_eq!(
+                adj.val.map(|x| x.eps[i]),
+                t.adjoint().val * ad,
+                epsilon = 1e-12
+            );
+        }
+    }
+}
@@ -0,0 +1,477 @@
+//! Trajectory evaluation, requires the `alloc` feature
+//!
+//! the absolute trajectory error (ATE) compares the poses after aligning the estimated positions
+//! onto the reference positions by the method of Umeyama,
+//! the relative pose error (RPE) compares the relative motions over a fixed number of frames,
+//! a time delta or a travelled distance, it measures the drift.
+//! poses are associated by their timestamps.
+//! rotation errors are the angles of the rotation part of `log(A⁻¹ B)`.
+//!
+//! ```rust
+//! use liealg::eval::{ate, EvalOptions};
+//! use liealg::trajectory::Stamped;
+//! use liealg::{SE3, SO3};
+//!
+//! let reference: Vec<_> = (0..10)
+//!     .map(|i| {
+//!         let t = i as f64 * 0.3;
+//!         Stamped {
+//!             time: i as f64 * 0.1,
+//!             pose: SE3::new(&SO3::from_euler_angles(0., 0., t), [t.cos(), t.sin(), 0.1 * t]),
+//!         }
+//!     })
+//!     .collect();
+//! let ate = ate(&reference, &reference, &EvalOptions::default()).unwrap();
+//! assert!(ate.translation.rmse < 1e-9);
+//! ```
+
+use core::{cmp::Ordering, fmt::Display};
+
+use alloc::{vec, vec::Vec};
+use nalgebra::{RealField, Vector3};
+
+use crate::{
+    registration::{umeyama, umeyama_similarity, RegistrationError, Similarity},
+    trajectory::Stamped,
+    Algebra, Group, Point, Real, SE3,
+};
+
+/// alignment of the estimated trajectory onto the reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// compare the trajectories as they are
+    None,
+    /// rigid transform
+    Se3,
+    /// similarity transform, for monocular trajectories with an unknown scale
+    Sim3,
+}
+
+/// options of the evaluation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalOptions<T> {
+    /// maximum difference between the timestamps of associated poses
+    pub max_time_difference: T,
+    /// alignment of the estimated trajectory
+    pub alignment: Alignment,
+}
+
+impl<T: Real> Default for EvalOptions<T> {
+    fn default() -> Self {
+        Self {
+            max_time_difference: T::from(0.02).unwrap(),
+            alignment: Alignment::Se3,
+        }
+    }
+}
+
+/// interval of the relative pose error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delta<T> {
+    /// number of associated poses, at least 1
+    Frames(usize),
+    /// time difference, each segment ends at the first pose at least this late
+    Time(T),
+    /// distance travelled along the reference, each segment ends at the first pose at least this far
+    Distance(T),
+}
+
+/// statistics of a set of errors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics<T> {
+    /// root mean square
+    pub rmse: T,
+    /// mean
+    pub mean: T,
+    /// median, the mean of the two middle errors for an even count
+    pub median: T,
+    /// maximum
+    pub max: T,
+}
+
+impl<T: Real> Statistics<T> {
+    /// statistics of the errors, `None` if there is none
+    pub fn new(errors: &[T]) -> Option<Self> {
+        if errors.is_empty() {
+            return None;
+        }
+        let mut sorted = errors.to_vec();
+        sorted.sort_by(by_value);
+        let n = sorted.len();
+        let count = T::from(n).unwrap();
+        let two = T::one() + T::one();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / two
+        };
+        Some(Self {
+            rmse: (sorted.iter().fold(T::zero(), |s, e| s + *e * *e) / count).sqrt(),
+            mean: sorted.iter().fold(T::zero(), |s, e| s + *e) / count,
+            median,
+            max: sorted[n - 1],
+        })
+    }
+}
+
+/// absolute trajectory error
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ate<T> {
+    /// transform mapping the estimated trajectory onto the reference
+    pub alignment: Similarity<T>,
+    /// associated `(reference, estimate)` indices, sorted by time
+    pub pairs: Vec<(usize, usize)>,
+    /// position errors
+    pub translation: Statistics<T>,
+    /// rotation angle errors, in radians
+    pub rotation: Statistics<T>,
+}
+
+/// relative pose error
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rpe<T> {
+    /// transform mapping the estimated trajectory onto the reference, only its scale matters
+    pub alignment: Similarity<T>,
+    /// number of compared segments
+    pub segments: usize,
+    /// errors of the relative translations
+    pub translation: Statistics<T>,
+    /// rotation angle errors of the relative motions, in radians
+    pub rotation: Statistics<T>,
+}
+
+/// error of the evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalError {
+    /// too few associated poses, or no segment for the relative pose error
+    TooFewPairs,
+    /// the associated positions are collinear, the alignment is not unique
+    Degenerate,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EvalError::TooFewPairs => write!(f, "too few associated poses"),
+            EvalError::Degenerate => write!(f, "degenerate trajectory for the alignment"),
+        }
+    }
+}
+
+fn by_value<T: Real>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+/// associate the poses with the closest timestamps, at most `max_difference` apart
+///
+/// each pose is used at most once, the closest pairs are associated first.
+/// returns the `(reference, estimate)` indices sorted by time, the inputs need not be sorted
+pub fn associate<T: Real>(
+    reference: &[Stamped<T>],
+    estimate: &[Stamped<T>],
+    max_difference: T,
+) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..reference.len()).collect();
+    order.sort_by(|&a, &b| by_value(&reference[a].time, &reference[b].time));
+    // the reference poses just before and after each estimated pose
+    let mut candidates = Vec::new();
+    for (j, e) in estimate.iter().enumerate() {
+        let k = order.partition_point(|&i| reference[i].time < e.time);
+        for &i in &order[k.saturating_sub(1)..(k + 1).min(order.len())] {
+            let d = (reference[i].time - e.time).abs();
+            if d <= max_difference {
+                candidates.push((d, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| by_value(&a.0, &b.0));
+    let mut used_reference = vec![false; reference.len()];
+    let mut used_estimate = vec![false; estimate.len()];
+    let mut pairs = Vec::new();
+    for (_, i, j) in candidates {
+        if !used_reference[i] && !used_estimate[j] {
+            used_reference[i] = true;
+            used_estimate[j] = true;
+            pairs.push((i, j));
+        }
+    }
+    pairs.sort_by(|a, b| by_value(&reference[a.0].time, &reference[b.0].time));
+    pairs
+}
+
+/// absolute trajectory error of the estimate against the reference
+///
+/// the SE3 and Sim3 alignments require at least 3 associated poses which are not collinear
+pub fn ate<T: Real + RealField>(
+    reference: &[Stamped<T>],
+    estimate: &[Stamped<T>],
+    options: &EvalOptions<T>,
+) -> Result<Ate<T>, EvalError> {
+    let pairs = associate(reference, estimate, options.max_time_difference);
+    let alignment = align(reference, estimate, &pairs, options.alignment)?;
+    let (translation, rotation): (Vec<_>, Vec<_>) = pairs
+        .iter()
+        .map(|&(i, j)| {
+            let aligned = transform(&alignment, &estimate[j].pose);
+            pose_error(&reference[i].pose, &aligned)
+        })
+        .unzip();
+    Ok(Ate {
+        alignment,
+        pairs,
+        translation: Statistics::new(&translation).ok_or(EvalError::TooFewPairs)?,
+        rotation: Statistics::new(&rotation).ok_or(EvalError::TooFewPairs)?,
+    })
+}
+
+/// relative pose error of the estimate against the reference
+///
+/// each segment from an associated pose compares the relative motions
+/// `(Rᵢ⁻¹ Rⱼ)⁻¹ (Eᵢ⁻¹ Eⱼ)` of the reference and of the aligned estimate,
+/// a rigid alignment does not change the errors, a Sim3 alignment corrects the scale
+pub fn rpe<T: Real + RealField>(
+    reference: &[Stamped<T>],
+    estimate: &[Stamped<T>],
+    delta: Delta<T>,
+    options: &EvalOptions<T>,
+) -> Result<Rpe<T>, EvalError> {
+    let pairs = associate(reference, estimate, options.max_time_difference);
+    let alignment = align(reference, estimate, &pairs, options.alignment)?;
+    let refs: Vec<_> = pairs.iter().map(|&(i, _)| &reference[i]).collect();
+    let ests: Vec<_> = pairs
+        .iter()
+        .map(|&(_, j)| transform(&alignment, &estimate[j].pose))
+        .collect();
+    // distance travelled along the reference
+    let mut travelled = vec![T::zero(); refs.len()];
+    for k in 1..refs.len() {
+        let step = refs[k].pose.rp().1 - refs[k - 1].pose.rp().1;
+        travelled[k] = travelled[k - 1] + length(&step);
+    }
+    let mut translation = Vec::new();
+    let mut rotation = Vec::new();
+    for k in 0..refs.len() {
+        let end = match delta {
+            Delta::Frames(n) => k + n,
+            Delta::Time(dt) => {
+                k + 1 + refs[k + 1..].partition_point(|s| s.time - refs[k].time < dt)
+            }
+            Delta::Distance(d) => {
+                k + 1 + travelled[k + 1..].partition_point(|&t| t - travelled[k] < d)
+            }
+        };
+        if end <= k || end >= refs.len() {
+            continue;
+        }
+        let motion = refs[k].pose.inv().mat_mul(&refs[end].pose);
+        let estimated = ests[k].inv().mat_mul(&ests[end]);
+        let (t, r) = pose_error(&motion, &estimated);
+        translation.push(t);
+        rotation.push(r);
+    }
+    Ok(Rpe {
+        alignment,
+        segments: translation.len(),
+        translation: Statistics::new(&translation).ok_or(EvalError::TooFewPairs)?,
+        rotation: Statistics::new(&rotation).ok_or(EvalError::TooFewPairs)?,
+    })
+}
+
+/// transform mapping the associated estimated positions onto the reference positions
+fn align<T: Real + RealField>(
+    reference: &[Stamped<T>],
+    estimate: &[Stamped<T>],
+    pairs: &[(usize, usize)],
+    alignment: Alignment,
+) -> Result<Similarity<T>, EvalError> {
+    let position = |s: &Stamped<T>| Point { val: s.pose.rp().1 };
+    let src: Vec<_> = pairs.iter().map(|&(_, j)| position(&estimate[j])).collect();
+    let dst: Vec<_> = pairs
+        .iter()
+        .map(|&(i, _)| position(&reference[i]))
+        .collect();
+    let result = match alignment {
+        Alignment::None => Ok(Similarity {
+            pose: SE3::identity(),
+            scale: T::one(),
+        }),
+        Alignment::Se3 => umeyama(&src, &dst, None).map(|pose| Similarity {
+            pose,
+            scale: T::one(),
+        }),
+        Alignment::Sim3 => umeyama_similarity(&src, &dst, None),
+    };
+    result.map_err(|e| match e {
+        RegistrationError::Degenerate => EvalError::Degenerate,
+        _ => EvalError::TooFewPairs,
+    })
+}
+
+/// pose `s R_a R, s R_a p + t_a` of the similarity applied to the pose `R, p`
+fn transform<T: Real>(sim: &Similarity<T>, pose: &SE3<T>) -> SE3<T> {
+    let (ra, ta) = sim.pose.rp();
+    let (r, p) = pose.rp();
+    SE3::from_rp(&(ra * r), &(ra * p * sim.scale + ta))
+}
+
+/// translation and rotation angle of `A⁻¹ B`
+fn pose_error<T: Real>(a: &SE3<T>, b: &SE3<T>) -> (T, T) {
+    let error = a.inv().mat_mul(b);
+    let w = error.log().vee().val.fixed_rows::<3>(0).into_owned();
+    (length(&error.rp().1), length(&w))
+}
+
+fn length<T: Real>(v: &Vector3<T>) -> T {
+    v.dot(v).sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::SO3;
+
+    /// a helix, 0.1 s and 1 m apart
+    fn reference() -> Vec<Stamped<f64>> {
+        (0..20)
+            .map(|i| {
+                let t = i as f64;
+                let yaw = 0.3 * t;
+                Stamped {
+                    time: 100. + 0.1 * t,
+                    pose: SE3::new(
+                        &SO3::from_euler_angles(0.1, -0.05 * t, yaw),
+                        [yaw.cos() / 0.3, yaw.sin() / 0.3, 0.1 * t],
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    fn similarity() -> Similarity<f64> {
+        Similarity {
+            pose: SE3::new(&SO3::from_euler_angles(0.4, -0.2, 1.), [1., -2., 0.5]),
+            scale: 0.5,
+        }
+    }
+
+    /// the reference seen through the inverse of the similarity, with jittered timestamps
+    fn estimate(scale: f64) -> Vec<Stamped<f64>> {
+        let sim = similarity();
+        let inv = Similarity {
+            pose: SE3::new(&SO3::identity(), [0.; 3]),
+            scale: scale / sim.scale,
+        };
+        reference()
+            .into_iter()
+            .enumerate()
+            .rev()
+            .map(|(i, s)| Stamped {
+                time: s.time + if i % 2 == 0 { 0.004 } else { -0.007 },
+                pose: transform(&inv, &sim.pose.inv().mat_mul(&s.pose)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_statistics() {
+        let stats = Statistics::new(&[4., 1., 3., 2.]).unwrap();
+        assert_relative_eq!(stats.rmse, 7.5f64.sqrt());
+        assert_eq!((stats.mean, stats.median, stats.max), (2.5, 2.5, 4.));
+        assert_eq!(Statistics::new(&[3., 1., 2.]).unwrap().median, 2.);
+        assert_eq!(Statistics::<f64>::new(&[]), None);
+    }
+
+    #[test]
+    fn test_associate() {
+        let reference = reference();
+        let estimate = estimate(1.);
+        let pairs = associate(&reference, &estimate, 0.02);
+        let n = reference.len();
+        assert_eq!(pairs.len(), n);
+        for (k, &(i, j)) in pairs.iter().enumerate() {
+            assert_eq!((i, j), (k, n - 1 - k));
+        }
+        // the jitter exceeds the tolerance of the odd poses
+        assert_eq!(associate(&reference, &estimate, 0.005).len(), n / 2);
+        assert!(associate(&reference, &estimate[..0], 0.02).is_empty());
+    }
+
+    #[test]
+    fn test_ate() {
+        let reference = reference();
+        let options = |alignment| EvalOptions {
+            alignment,
+            ..Default::default()
+        };
+        // the estimate is only off by a similarity
+        let ate = super::ate(&reference, &estimate(1.), &options(Alignment::Sim3)).unwrap();
+        assert_eq!(ate.pairs.len(), reference.len());
+        assert!(ate.translation.max < 1e-9);
+        assert!(ate.rotation.max < 1e-9);
+        assert_relative_eq!(ate.alignment.scale, similarity().scale, epsilon = 1e-9);
+        assert_relative_eq!(ate.alignment.pose, similarity().pose, epsilon = 1e-9);
+
+        // with the right scale, a rigid alignment is enough
+        let estimate = estimate(similarity().scale);
+        let ate = super::ate(&reference, &estimate, &options(Alignment::Se3)).unwrap();
+        assert!(ate.translation.max < 1e-9);
+        assert_eq!(ate.alignment.scale, 1.);
+        let ate = super::ate(&reference, &estimate, &options(Alignment::None)).unwrap();
+        assert!(ate.translation.rmse > 1.);
+        assert!(ate.rotation.rmse > 0.1);
+
+        assert_eq!(
+            super::ate(&reference, &estimate[..2], &options(Alignment::Se3)),
+            Err(EvalError::TooFewPairs)
+        );
+        assert_eq!(
+            super::ate(&reference, &[], &options(Alignment::None)),
+            Err(EvalError::TooFewPairs)
+        );
+    }
+
+    #[test]
+    fn test_rpe() {
+        let reference = reference();
+        let n = reference.len();
+        let mut estimate = reference.clone();
+        // a single pose off by 0.1 m in its own frame
+        let offset = SE3::new(&SO3::identity(), [0., 0.1, 0.]);
+        estimate[5].pose = estimate[5].pose.mat_mul(&offset);
+        let options = EvalOptions {
+            alignment: Alignment::None,
+            ..Default::default()
+        };
+        let rpe = super::rpe(&reference, &estimate, Delta::Frames(1), &options).unwrap();
+        assert_eq!(rpe.segments, n - 1);
+        // the two segments around the pose
+        assert_relative_eq!(rpe.translation.max, 0.1, epsilon = 1e-12);
+        assert_relative_eq!(
+            rpe.translation.rmse,
+            (2. * 0.01 / (n - 1) as f64).sqrt(),
+            epsilon = 1e-12
+        );
+        assert!(rpe.rotation.max < 1e-12);
+
+        // segments ending 3 poses later, 0.3 s and about 3 m
+        let rpe = super::rpe(&reference, &estimate, Delta::Time(0.25), &options).unwrap();
+        assert_eq!(rpe.segments, n - 3);
+        let rpe = super::rpe(&reference, &estimate, Delta::Distance(2.5), &options).unwrap();
+        assert_eq!(rpe.segments, n - 3);
+
+        // the scale of a Sim3 alignment is corrected
+        let options = EvalOptions {
+            alignment: Alignment::Sim3,
+            ..Default::default()
+        };
+        let rpe = super::rpe(&reference, &self::estimate(2.), Delta::Frames(3), &options).unwrap();
+        assert_eq!(rpe.segments, n - 3);
+        assert!(rpe.translation.max < 1e-9 && rpe.rotation.max < 1e-9);
+        assert_eq!(
+            super::rpe(&reference, &estimate, Delta::Frames(n), &options),
+            Err(EvalError::TooFewPairs)
+        );
+    }
+}
@@ -0,0 +1,721 @@
+//! Tree of named frames connected by SE3 transforms, like ROS tf
+//!
+//! each frame has at most one parent, the edge from a child to its parent holds `T_parent_child`,
+//! the pose of the child in the parent frame. static edges hold a constant transform,
+//! time-varying edges keep a bounded [History] of stamped transforms and interpolate between them.
+//! the transform between two frames composes the edges up to their lowest common ancestor.
+//!
+//! the frames are kept in a [Storage], a `Vec` with the `alloc` feature,
+//! or an [ArrayStorage] of fixed capacity which does not allocate.
+//! the histories are a [RingBuffer] of the `N` latest transforms which does not allocate,
+//! or a [TimeWindow] of the transforms of the last seconds with the `alloc` feature.
+//!
+//! ```rust
+//! use liealg::frames::{ArrayStorage, FrameTree, RingBuffer};
+//! use liealg::prelude::*;
+//! use liealg::{Point, SE3, SO3};
+//!
+//! let mut tree = FrameTree::<&str, f64, ArrayStorage<_, 8>, RingBuffer<_, 16>>::new();
+//! let camera = SE3::new(&SO3::from_euler_angles(0., 0.5, 0.), [0.2, 0., 0.5]);
+//! tree.add_static("camera", "base", camera).unwrap();
+//! tree.add_dynamic("base", "world", RingBuffer::new()).unwrap();
+//! for k in 0..20 {
+//!     let t = k as f64 * 0.1;
+//!     tree.update(&"base", t, SE3::new(&SO3::identity(), [t, 0., 0.])).unwrap();
+//! }
+//! // a camera stamp behind the latest odometry
+//! let world_camera = tree.lookup(&"world", &"camera", 1.25).unwrap();
+//! let p = world_camera.act(&Point::new(0., 0., 1.));
+//! ```
+
+use core::{fmt::Display, marker::PhantomData};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::trajectory::Trajectory;
+use crate::{trajectory::Stamped, Group, Real, SE3};
+
+/// storage of the frames of a [FrameTree]
+pub trait Storage<E> {
+    /// number of elements
+    fn len(&self) -> usize;
+    /// whether there is no element
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// maximum number of elements
+    fn capacity(&self) -> usize;
+    /// element at the index
+    fn get(&self, index: usize) -> Option<&E>;
+    /// mutable element at the index
+    fn get_mut(&mut self, index: usize) -> Option<&mut E>;
+    /// append an element, returned back if the storage is full
+    fn push(&mut self, element: E) -> Result<(), E>;
+}
+
+#[cfg(feature = "alloc")]
+impl<E> Storage<E> for Vec<E> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        usize::MAX
+    }
+
+    fn get(&self, index: usize) -> Option<&E> {
+        self.as_slice().get(index)
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut E> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        Vec::push(self, element);
+        Ok(())
+    }
+}
+
+/// storage of at most `N` elements, without allocation
+#[derive(Debug, Clone)]
+pub struct ArrayStorage<E, const N: usize> {
+    elements: [Option<E>; N],
+    len: usize,
+}
+
+impl<E, const N: usize> Default for ArrayStorage<E, N> {
+    fn default() -> Self {
+        Self {
+            elements: core::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+}
+
+impl<E, const N: usize> Storage<E> for ArrayStorage<E, N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn get(&self, index: usize) -> Option<&E> {
+        self.elements.get(index)?.as_ref()
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut E> {
+        self.elements.get_mut(index)?.as_mut()
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        match self.elements.get_mut(self.len) {
+            Some(slot) => {
+                *slot = Some(element);
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(element),
+        }
+    }
+}
+
+/// stamped transforms of a time-varying edge, in time order
+pub trait History<T> {
+    /// latest transform
+    fn latest(&self) -> Option<&Stamped<T>>;
+    /// append a transform later than the latest one, the oldest ones beyond the bound are dropped
+    fn push(&mut self, stamped: Stamped<T>);
+    /// replace the latest transform
+    fn replace_latest(&mut self, stamped: Stamped<T>);
+    /// transform at the time, interpolated between the neighbouring transforms,
+    /// `None` outside of the kept transforms
+    fn interpolate(&self, time: T) -> Option<SE3<T>>;
+}
+
+/// the `N` latest transforms, without allocation
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T, const N: usize> {
+    elements: [Option<Stamped<T>>; N],
+    // index of the oldest element
+    start: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self {
+            elements: core::array::from_fn(|_| None),
+            start: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    /// empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// number of transforms
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// whether there is no transform
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// transforms from the oldest to the latest
+    pub fn iter(&self) -> impl Iterator<Item = &Stamped<T>> + '_ {
+        (0..self.len).map(|k| self.get(k).unwrap())
+    }
+
+    /// `k`-th oldest transform
+    fn get(&self, k: usize) -> Option<&Stamped<T>> {
+        if k < self.len {
+            self.elements[(self.start + k) % N].as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Real, const N: usize> History<T> for RingBuffer<T, N> {
+    fn latest(&self) -> Option<&Stamped<T>> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    fn push(&mut self, stamped: Stamped<T>) {
+        if N == 0 {
+            return;
+        }
+        if self.len < N {
+            self.elements[(self.start + self.len) % N] = Some(stamped);
+            self.len += 1;
+        } else {
+            // overwrite the oldest
+            self.elements[self.start] = Some(stamped);
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    fn replace_latest(&mut self, stamped: Stamped<T>) {
+        match self.len.checked_sub(1) {
+            Some(k) => self.elements[(self.start + k) % N] = Some(stamped),
+            None => self.push(stamped),
+        }
+    }
+
+    fn interpolate(&self, time: T) -> Option<SE3<T>> {
+        // the latest transform which is not later, lookups are usually close to the latest
+        let k = (0..self.len)
+            .rev()
+            .find(|&k| self.get(k).unwrap().time <= time)?;
+        let a = self.get(k).unwrap();
+        if a.time == time {
+            return Some(a.pose.clone());
+        }
+        Some(a.interpolate(self.get(k + 1)?, time))
+    }
+}
+
+/// the transforms of the last `duration` before the latest one
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct TimeWindow<T> {
+    trajectory: Trajectory<T>,
+    duration: T,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> TimeWindow<T> {
+    /// empty window keeping the transforms of the last `duration`
+    pub fn new(duration: T) -> Self {
+        Self {
+            trajectory: Trajectory::new(),
+            duration,
+        }
+    }
+
+    /// kept transforms
+    pub fn trajectory(&self) -> &Trajectory<T> {
+        &self.trajectory
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> History<T> for TimeWindow<T> {
+    fn latest(&self) -> Option<&Stamped<T>> {
+        self.trajectory.as_slice().last()
+    }
+
+    fn push(&mut self, stamped: Stamped<T>) {
+        let start = stamped.time - self.duration;
+        self.trajectory.push(stamped.time, stamped.pose);
+        self.trajectory.truncate_before(start);
+    }
+
+    fn replace_latest(&mut self, stamped: Stamped<T>) {
+        self.trajectory.pop();
+        self.push(stamped);
+    }
+
+    fn interpolate(&self, time: T) -> Option<SE3<T>> {
+        self.trajectory.interpolate(time)
+    }
+}
+
+/// edge from a frame to its parent
+#[derive(Debug, Clone)]
+enum Link<T, H> {
+    Static(SE3<T>),
+    Dynamic(H),
+}
+
+/// named frame of a [FrameTree], with the histories `H` of time-varying edges
+#[derive(Debug, Clone)]
+pub struct Frame<K, T, H = RingBuffer<T, 16>> {
+    name: K,
+    parent: Option<(usize, Link<T, H>)>,
+}
+
+/// error of the frame tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// no frame has this name
+    UnknownFrame,
+    /// the frame already has a parent
+    HasParent,
+    /// the edge would close a cycle
+    Cycle,
+    /// the storage is full
+    Full,
+    /// the frames are in different trees
+    NotConnected,
+    /// the frame has no time-varying edge to its parent
+    NotDynamic,
+    /// the transform is older than the latest one of the edge
+    OutOfOrder,
+    /// a time-varying edge has no transform yet
+    NoData,
+    /// the time is outside of the transforms kept by a time-varying edge
+    Extrapolation,
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FrameError::UnknownFrame => write!(f, "unknown frame"),
+            FrameError::HasParent => write!(f, "the frame already has a parent"),
+            FrameError::Cycle => write!(f, "the edge would close a cycle"),
+            FrameError::Full => write!(f, "the frame storage is full"),
+            FrameError::NotConnected => write!(f, "the frames are not connected"),
+            FrameError::NotDynamic => write!(f, "the frame has no time-varying edge"),
+            FrameError::OutOfOrder => write!(f, "the transform is older than the latest one"),
+            FrameError::NoData => write!(f, "no transform for the time-varying edge"),
+            FrameError::Extrapolation => write!(f, "the time is outside of the kept transforms"),
+        }
+    }
+}
+
+/// tree of named frames stored in `S`, with the histories `H` of time-varying edges,
+/// see the [module](self) documentation
+#[derive(Debug, Clone)]
+pub struct FrameTree<K, T, S, H = RingBuffer<T, 16>> {
+    frames: S,
+    marker: PhantomData<(K, T, H)>,
+}
+
+impl<K, T, S: Default, H> Default for FrameTree<K, T, S, H> {
+    fn default() -> Self {
+        Self {
+            frames: S::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, T, S, H> FrameTree<K, T, S, H>
+where
+    K: PartialEq,
+    T: Real,
+    S: Storage<Frame<K, T, H>>,
+    H: History<T>,
+{
+    /// empty tree
+    pub fn new() -> Self
+    where
+        S: Default,
+    {
+        Self::default()
+    }
+
+    /// number of frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// whether there is no frame
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// whether a frame has this name
+    pub fn contains(&self, name: &K) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// name of the parent of the frame
+    pub fn parent(&self, name: &K) -> Option<&K> {
+        let (parent, _) = self.frame(self.find(name)?).parent.as_ref()?;
+        Some(&self.frame(*parent).name)
+    }
+
+    /// add an edge with the constant pose `T_parent_child` of the child in the parent frame,
+    /// the frames are created if needed
+    pub fn add_static(&mut self, child: K, parent: K, transform: SE3<T>) -> Result<(), FrameError> {
+        self.add_link(child, parent, Link::Static(transform))
+    }
+
+    /// add a time-varying edge keeping its transforms in the history,
+    /// the transforms are given by [update](Self::update)
+    pub fn add_dynamic(&mut self, child: K, parent: K, history: H) -> Result<(), FrameError> {
+        self.add_link(child, parent, Link::Dynamic(history))
+    }
+
+    /// set the pose `T_parent_child` at the time of the time-varying edge of the child,
+    /// the time must not be older than the latest one
+    pub fn update(&mut self, child: &K, time: T, transform: SE3<T>) -> Result<(), FrameError> {
+        let i = self.find(child).ok_or(FrameError::UnknownFrame)?;
+        let frame = self.frames.get_mut(i).unwrap();
+        let Some((_, Link::Dynamic(history))) = &mut frame.parent else {
+            return Err(FrameError::NotDynamic);
+        };
+        let stamped = Stamped {
+            time,
+            pose: transform,
+        };
+        match history.latest().map(|l| l.time) {
+            Some(latest) if time < latest => return Err(FrameError::OutOfOrder),
+            Some(latest) if time == latest => history.replace_latest(stamped),
+            _ => history.push(stamped),
+        }
+        Ok(())
+    }
+
+    /// transform `T_target_source` mapping coordinates in the source frame to the target frame at the time
+    pub fn lookup(&self, target: &K, source: &K, time: T) -> Result<SE3<T>, FrameError> {
+        self.lookup_at(target, source, Some(time))
+    }
+
+    /// transform `T_target_source` with the latest transform of each time-varying edge
+    pub fn lookup_latest(&self, target: &K, source: &K) -> Result<SE3<T>, FrameError> {
+        self.lookup_at(target, source, None)
+    }
+
+    fn lookup_at(&self, target: &K, source: &K, time: Option<T>) -> Result<SE3<T>, FrameError> {
+        let target = self.find(target).ok_or(FrameError::UnknownFrame)?;
+        let source = self.find(source).ok_or(FrameError::UnknownFrame)?;
+        // the lowest ancestor of the source which is an ancestor of the target
+        let common = self
+            .ancestors(source)
+            .find(|&a| self.ancestors(target).any(|b| b == a))
+            .ok_or(FrameError::NotConnected)?;
+        let common_target = self.to_ancestor(target, common, time)?;
+        let common_source = self.to_ancestor(source, common, time)?;
+        Ok(common_target.inv().mat_mul(&common_source))
+    }
+
+    fn add_link(&mut self, child: K, parent: K, link: Link<T, H>) -> Result<(), FrameError> {
+        if child == parent {
+            return Err(FrameError::Cycle);
+        }
+        let (c, p) = (self.find(&child), self.find(&parent));
+        if let Some(c) = c {
+            if self.frame(c).parent.is_some() {
+                return Err(FrameError::HasParent);
+            }
+            // the child is a root, the edge closes a cycle if it is an ancestor of the parent
+            if p.is_some_and(|p| self.ancestors(p).any(|a| a == c)) {
+                return Err(FrameError::Cycle);
+            }
+        }
+        let new = usize::from(c.is_none()) + usize::from(p.is_none());
+        if self.frames.len() + new > self.frames.capacity() {
+            return Err(FrameError::Full);
+        }
+        let p = match p {
+            Some(p) => p,
+            None => self.push(parent)?,
+        };
+        let c = match c {
+            Some(c) => c,
+            None => self.push(child)?,
+        };
+        self.frames.get_mut(c).unwrap().parent = Some((p, link));
+        Ok(())
+    }
+
+    fn push(&mut self, name: K) -> Result<usize, FrameError> {
+        self.frames
+            .push(Frame { name, parent: None })
+            .map_err(|_| FrameError::Full)?;
+        Ok(self.frames.len() - 1)
+    }
+
+    fn find(&self, name: &K) -> Option<usize> {
+        (0..self.frames.len()).find(|&i| self.frame(i).name == *name)
+    }
+
+    fn frame(&self, i: usize) -> &Frame<K, T, H> {
+        self.frames.get(i).unwrap()
+    }
+
+    /// the frame and its ancestors up to the root
+    fn ancestors(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        core::iter::successors(Some(i), |&i| self.frame(i).parent.as_ref().map(|(p, _)| *p))
+    }
+
+    /// pose `T_ancestor_frame` of the frame in its ancestor
+    fn to_ancestor(
+        &self,
+        mut i: usize,
+        ancestor: usize,
+        time: Option<T>,
+    ) -> Result<SE3<T>, FrameError> {
+        let mut pose = SE3::identity();
+        while i != ancestor {
+            let (parent, link) = self.frame(i).parent.as_ref().unwrap();
+            pose = transform_at(link, time)?.mat_mul(&pose);
+            i = *parent;
+        }
+        Ok(pose)
+    }
+}
+
+/// transform of the edge at the time, or the latest one
+fn transform_at<T: Real, H: History<T>>(
+    link: &Link<T, H>,
+    time: Option<T>,
+) -> Result<SE3<T>, FrameError> {
+    let history = match link {
+        Link::Static(pose) => return Ok(pose.clone()),
+        Link::Dynamic(history) => history,
+    };
+    let latest = history.latest().ok_or(FrameError::NoData)?;
+    match time {
+        None => Ok(latest.pose.clone()),
+        Some(time) => history.interpolate(time).ok_or(FrameError::Extrapolation),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{Algebra, Vec6, Vector, SO3};
+
+    fn camera() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(-1.5, 0., -1.5), [0.2, 0., 0.5])
+    }
+
+    fn lidar() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(0., 0., 3.), [0., 0.1, 0.8])
+    }
+
+    fn odom(t: f64) -> SE3<f64> {
+        (Vec6::new([0., 0., 0.3], [1., 0., 0.]) * t).hat().exp()
+    }
+
+    /// world <- map <- odom <- base <- camera, lidar
+    fn robot<S: Storage<Frame<&'static str, f64>> + Default>() -> FrameTree<&'static str, f64, S> {
+        let mut tree = FrameTree::new();
+        tree.add_static("camera", "base", camera()).unwrap();
+        tree.add_static("lidar", "base", lidar()).unwrap();
+        tree.add_dynamic("base", "odom", RingBuffer::new()).unwrap();
+        tree.add_static("odom", "map", SE3::new(&SO3::identity(), [5., 0., 0.]))
+            .unwrap();
+        tree.add_dynamic("map", "world", RingBuffer::new()).unwrap();
+        for t in [0., 1.] {
+            tree.update(&"base", t, odom(t)).unwrap();
+        }
+        tree
+    }
+
+    fn check<S: Storage<Frame<&'static str, f64>>>(tree: &FrameTree<&'static str, f64, S>) {
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.parent(&"camera"), Some(&"base"));
+        assert_eq!(tree.parent(&"world"), None);
+        // static path, the time-varying edges above base are not needed
+        let lidar_camera = tree.lookup(&"lidar", &"camera", 5.).unwrap();
+        assert_relative_eq!(
+            lidar_camera,
+            lidar().inv().mat_mul(&camera()),
+            epsilon = 1e-12
+        );
+        // interpolated time-varying edge
+        let odom_camera = tree.lookup(&"odom", &"camera", 0.4).unwrap();
+        assert_relative_eq!(odom_camera, odom(0.4).mat_mul(&camera()), epsilon = 1e-12);
+        let camera_odom = tree.lookup_latest(&"camera", &"odom").unwrap();
+        assert_relative_eq!(
+            camera_odom,
+            odom(1.).mat_mul(&camera()).inv(),
+            epsilon = 1e-12
+        );
+        assert_eq!(
+            tree.lookup(&"odom", &"camera", 1.5),
+            Err(FrameError::Extrapolation)
+        );
+        assert_eq!(
+            tree.lookup(&"world", &"camera", 0.5),
+            Err(FrameError::NoData)
+        );
+        assert_eq!(tree.lookup(&"camera", &"camera", 0.5), Ok(SE3::identity()));
+    }
+
+    #[test]
+    fn test_lookup() {
+        check(&robot::<ArrayStorage<_, 6>>());
+        #[cfg(feature = "alloc")]
+        check(&robot::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_edges() {
+        let mut tree = robot::<ArrayStorage<_, 7>>();
+        assert_eq!(
+            tree.add_static("lidar", "camera", lidar()),
+            Err(FrameError::HasParent)
+        );
+        assert_eq!(
+            tree.add_static("world", "lidar", lidar()),
+            Err(FrameError::Cycle)
+        );
+        assert_eq!(
+            tree.add_static("imu", "imu", lidar()),
+            Err(FrameError::Cycle)
+        );
+        // two new frames do not fit
+        assert_eq!(
+            tree.add_static("gps", "antenna", lidar()),
+            Err(FrameError::Full)
+        );
+        tree.add_static("gps", "base", lidar()).unwrap();
+        assert_eq!(tree.len(), 7);
+        assert_eq!(
+            tree.add_static("imu", "base", lidar()),
+            Err(FrameError::Full)
+        );
+        assert_eq!(tree.len(), 7);
+        assert!(!tree.contains(&"imu"));
+        assert_eq!(
+            tree.lookup(&"nowhere", &"gps", 0.),
+            Err(FrameError::UnknownFrame)
+        );
+
+        let mut tree = FrameTree::<_, f64, ArrayStorage<_, 4>>::new();
+        tree.add_static("a", "b", camera()).unwrap();
+        tree.add_static("c", "d", camera()).unwrap();
+        assert_eq!(tree.lookup(&"a", &"c", 0.), Err(FrameError::NotConnected));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut tree = robot::<ArrayStorage<_, 6>>();
+        assert_eq!(
+            tree.update(&"camera", 2., camera()),
+            Err(FrameError::NotDynamic)
+        );
+        assert_eq!(
+            tree.update(&"world", 2., camera()),
+            Err(FrameError::NotDynamic)
+        );
+        assert_eq!(
+            tree.update(&"imu", 2., camera()),
+            Err(FrameError::UnknownFrame)
+        );
+        assert_eq!(
+            tree.update(&"base", 0.5, camera()),
+            Err(FrameError::OutOfOrder)
+        );
+        // a repeated time replaces the latest transform
+        tree.update(&"base", 1., odom(2.)).unwrap();
+        assert_relative_eq!(
+            tree.lookup(&"odom", &"base", 1.).unwrap(),
+            odom(2.),
+            epsilon = 1e-12
+        );
+        tree.update(&"base", 3., odom(3.)).unwrap();
+        tree.lookup(&"odom", &"base", 0.5).unwrap();
+        tree.lookup(&"odom", &"base", 2.).unwrap();
+    }
+
+    /// odometry at 10 Hz, up to 0.9
+    fn odometry<H: History<f64>>(
+        history: H,
+    ) -> FrameTree<&'static str, f64, ArrayStorage<Frame<&'static str, f64, H>, 3>, H> {
+        let mut tree = FrameTree::new();
+        tree.add_static("camera", "base", camera()).unwrap();
+        tree.add_dynamic("base", "odom", history).unwrap();
+        for k in 0..10 {
+            let t = k as f64 * 0.1;
+            tree.update(&"base", t, odom(t)).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn test_history() {
+        let mut buffer = RingBuffer::<f64, 4>::new();
+        for k in 0..10 {
+            buffer.push(Stamped {
+                time: k as f64,
+                pose: odom(k as f64),
+            });
+        }
+        assert!(buffer.iter().map(|s| s.time).eq([6., 7., 8., 9.]));
+        buffer.replace_latest(Stamped {
+            time: 9.,
+            pose: SE3::identity(),
+        });
+        assert_eq!(buffer.latest().unwrap().pose, SE3::identity());
+        assert_eq!(buffer.len(), 4);
+
+        // a camera stamp several odometry updates behind the latest one
+        let tree = odometry(RingBuffer::<_, 4>::new());
+        assert_relative_eq!(
+            tree.lookup(&"odom", &"camera", 0.65).unwrap(),
+            odom(0.65).mat_mul(&camera()),
+            epsilon = 1e-12
+        );
+        // older than the 4 kept transforms
+        assert_eq!(
+            tree.lookup(&"odom", &"camera", 0.55),
+            Err(FrameError::Extrapolation)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_time_window() {
+        // 0.5 is kept to interpolate at the start of the window
+        let tree = odometry(TimeWindow::new(0.35));
+        assert_relative_eq!(
+            tree.lookup(&"odom", &"camera", 0.55).unwrap(),
+            odom(0.55).mat_mul(&camera()),
+            epsilon = 1e-12
+        );
+        assert_eq!(
+            tree.lookup(&"odom", &"camera", 0.45),
+            Err(FrameError::Extrapolation)
+        );
+    }
+}
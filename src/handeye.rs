@@ -0,0 +1,532 @@
+//! Hand-eye calibration
+//!
+//! `AX = XB` relates the motions `Aᵢ` of a robot flange to the motions `Bᵢ` of a camera mounted on it,
+//! `X` being the pose of the camera in the flange frame.
+//! `AX = YB` relates the flange poses `Aᵢ` in the robot base frame to the target poses `Bᵢ`
+//! in the camera frame, `X` being the pose of the target in the flange frame and `Y` the pose of the camera in the base frame.
+//!
+//! every solver takes the measurements as `(Aᵢ, Bᵢ)` pairs
+//!
+//! ```rust
+//! use liealg::handeye::{park_martin, refine_ax_xb, RefineOptions};
+//! use liealg::prelude::*;
+//! use liealg::{SE3, SO3};
+//!
+//! let x = SE3::new(&SO3::from_euler_angles(0.1, 0.2, 0.3), [0.1, 0., 0.05]);
+//! let pairs: Vec<_> = [[0.3, 0., 0.1], [0., 0.4, -0.2], [0.2, -0.3, 0.5]]
+//!     .into_iter()
+//!     .map(|rpy| {
+//!         let a = SE3::new(&SO3::from_euler_angles(rpy[0], rpy[1], rpy[2]), rpy);
+//!         let b = x.inv().mat_mul(&a).mat_mul(&x);
+//!         (a, b)
+//!     })
+//!     .collect();
+//! let calib = park_martin(&pairs).unwrap();
+//! let calib = refine_ax_xb(&pairs, &calib.x, &RefineOptions::default()).unwrap();
+//! assert!(calib.residuals.rotation_max < 1e-9);
+//! ```
+
+use core::fmt::Display;
+
+use nalgebra::{Matrix3, RealField, SMatrix, SVector, SymmetricEigen, Vector3, Vector6};
+
+use crate::{
+    registration::svd_rotation,
+    utils::{hat, solve_spd},
+    Algebra, Group, Real, Vec6, SE3, SO3,
+};
+
+/// error of the hand-eye solvers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandEyeError {
+    /// fewer pairs than the solver requires
+    TooFewPairs,
+    /// the motions do not determine the calibration, e.g. all rotations share an axis
+    Degenerate,
+}
+
+impl Display for HandEyeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HandEyeError::TooFewPairs => write!(f, "too few pairs"),
+            HandEyeError::Degenerate => write!(f, "degenerate motions"),
+        }
+    }
+}
+
+/// consistency of the calibration with the pairs,
+/// comparing the two sides of the equation `AX = XB` or `AX = YB`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Residuals<T> {
+    /// root mean square of the rotation angles between the two sides
+    pub rotation_rms: T,
+    /// maximum rotation angle between the two sides
+    pub rotation_max: T,
+    /// root mean square of the distances between the translations of the two sides
+    pub translation_rms: T,
+    /// maximum distance between the translations of the two sides
+    pub translation_max: T,
+}
+
+/// solution of `AX = XB`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandEye<T> {
+    /// calibrated pose
+    pub x: SE3<T>,
+    /// residuals of the pairs
+    pub residuals: Residuals<T>,
+    /// number of iterations, 0 for the closed form solvers
+    pub iterations: usize,
+    /// whether the last update is below the tolerance, true for the closed form solvers
+    pub converged: bool,
+}
+
+/// solution of `AX = YB`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobotWorld<T> {
+    /// calibrated pose on the right of `A`
+    pub x: SE3<T>,
+    /// calibrated pose on the left of `B`
+    pub y: SE3<T>,
+    /// residuals of the pairs
+    pub residuals: Residuals<T>,
+    /// number of iterations, 0 for the closed form solver
+    pub iterations: usize,
+    /// whether the last update is below the tolerance, true for the closed form solver
+    pub converged: bool,
+}
+
+/// options of the nonlinear refinement
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefineOptions<T> {
+    /// maximum number of Gauss-Newton iterations
+    pub max_iterations: usize,
+    /// the iteration stops once the norm of the update is below the tolerance
+    pub tolerance: T,
+}
+
+impl<T: Real> Default for RefineOptions<T> {
+    fn default() -> Self {
+        Self {
+            max_iterations: 20,
+            tolerance: T::epsilon().sqrt(),
+        }
+    }
+}
+
+fn residuals<T: Real>(
+    pairs: &[(SE3<T>, SE3<T>)],
+    lhs: impl Fn(&SE3<T>) -> SE3<T>,
+    rhs: impl Fn(&SE3<T>) -> SE3<T>,
+) -> Residuals<T> {
+    let mut res = Residuals {
+        rotation_rms: T::zero(),
+        rotation_max: T::zero(),
+        translation_rms: T::zero(),
+        translation_max: T::zero(),
+    };
+    for (a, b) in pairs {
+        let (r1, t1) = lhs(a).rp();
+        let (r2, t2) = rhs(b).rp();
+        let w = SO3 {
+            val: r1.transpose() * r2,
+        }
+        .log()
+        .vee()
+        .val;
+        let angle = w.dot(&w).sqrt();
+        let d = t1 - t2;
+        let dist = d.dot(&d).sqrt();
+        res.rotation_rms += angle * angle;
+        res.translation_rms += dist * dist;
+        res.rotation_max = res.rotation_max.max(angle);
+        res.translation_max = res.translation_max.max(dist);
+    }
+    let n = T::from(pairs.len()).unwrap();
+    res.rotation_rms = (res.rotation_rms / n).sqrt();
+    res.translation_rms = (res.translation_rms / n).sqrt();
+    res
+}
+
+fn hand_eye<T: Real>(
+    pairs: &[(SE3<T>, SE3<T>)],
+    x: SE3<T>,
+    iterations: usize,
+    converged: bool,
+) -> HandEye<T> {
+    let residuals = residuals(pairs, |a| a.mat_mul(&x), |b| x.mat_mul(b));
+    HandEye {
+        x,
+        residuals,
+        iterations,
+        converged,
+    }
+}
+
+/// translation of `AX = XB` given the rotation, least squares of `(R_A - I) t = R t_B - t_A`
+fn ax_xb_translation<T: Real>(
+    pairs: &[(SE3<T>, SE3<T>)],
+    r: &Matrix3<T>,
+) -> Result<Vector3<T>, HandEyeError> {
+    let mut h = Matrix3::zeros();
+    let mut g = Vector3::zeros();
+    for (a, b) in pairs {
+        let (ra, ta) = a.rp();
+        let (_, tb) = b.rp();
+        let c = ra - Matrix3::identity();
+        h += c.transpose() * c;
+        g += c.transpose() * (r * tb - ta);
+    }
+    solve_spd(&h, &g).ok_or(HandEyeError::Degenerate)
+}
+
+/// `AX = XB` by the method of Tsai and Lenz, with modified Rodrigues parameters of the rotations
+///
+/// requires at least 2 pairs with non parallel rotation axes
+pub fn tsai_lenz<T: Real>(pairs: &[(SE3<T>, SE3<T>)]) -> Result<HandEye<T>, HandEyeError> {
+    if pairs.len() < 2 {
+        return Err(HandEyeError::TooFewPairs);
+    }
+    let two = T::one() + T::one();
+    // 2 sin(θ/2) n
+    let rodrigues = |x: &SE3<T>| {
+        let w = SO3 { val: x.rp().0 }.log().vee().val;
+        let theta = w.dot(&w).sqrt();
+        if theta > T::zero() {
+            w * (two * (theta / two).sin() / theta)
+        } else {
+            w
+        }
+    };
+    let mut h = Matrix3::zeros();
+    let mut g = Vector3::zeros();
+    for (a, b) in pairs {
+        let (pa, pb) = (rodrigues(a), rodrigues(b));
+        let s = hat(&(pa + pb));
+        h += s.transpose() * s;
+        g += s.transpose() * (pb - pa);
+    }
+    let p = solve_spd(&h, &g).ok_or(HandEyeError::Degenerate)?;
+    let p = p * (two / (T::one() + p.dot(&p)).sqrt());
+    let p2 = p.dot(&p);
+    let four = two * two;
+    let r = Matrix3::identity() * (T::one() - p2 / two)
+        + (p * p.transpose() + hat(&p) * (four - p2).sqrt()) / two;
+    let t = ax_xb_translation(pairs, &r)?;
+    Ok(hand_eye(pairs, SE3::from_rp(&r, &t), 0, true))
+}
+
+/// `AX = XB` by the method of Park and Martin
+///
+/// the rotation vectors satisfy `log(R_A) = Ad(R_X) log(R_B) = R_X log(R_B)`,
+/// `R_X` is fitted to them in the least squares sense.
+/// requires at least 2 pairs with non parallel rotation axes
+pub fn park_martin<T: Real + RealField>(
+    pairs: &[(SE3<T>, SE3<T>)],
+) -> Result<HandEye<T>, HandEyeError> {
+    if pairs.len() < 2 {
+        return Err(HandEyeError::TooFewPairs);
+    }
+    let mut m = Matrix3::zeros();
+    for (a, b) in pairs {
+        let alpha = a.rot_trans().0.log().vee().val;
+        let beta = b.rot_trans().0.log().vee().val;
+        m += beta * alpha.transpose();
+    }
+    let (r, _) = svd_rotation(&m).map_err(|_| HandEyeError::Degenerate)?;
+    let t = ax_xb_translation(pairs, &r)?;
+    Ok(hand_eye(pairs, SE3::from_rp(&r, &t), 0, true))
+}
+
+/// `AX = XB` by Gauss-Newton, minimizing `Σ |log((AᵢX)⁻¹ X Bᵢ)|²` from an initial guess
+pub fn refine_ax_xb<T: Real>(
+    pairs: &[(SE3<T>, SE3<T>)],
+    initial: &SE3<T>,
+    options: &RefineOptions<T>,
+) -> Result<HandEye<T>, HandEyeError> {
+    if pairs.len() < 2 {
+        return Err(HandEyeError::TooFewPairs);
+    }
+    let mut x = initial.clone();
+    for iterations in 1..=options.max_iterations {
+        let mut h = SMatrix::<T, 6, 6>::zeros();
+        let mut g = Vector6::zeros();
+        for (a, b) in pairs {
+            let (mut j_ax, mut j_xb) = (SMatrix::zeros(), SMatrix::zeros());
+            let (mut j_p, mut j_q) = (SMatrix::zeros(), SMatrix::zeros());
+            let p = a.compose(&x, None, Some(&mut j_ax));
+            let q = x.compose(b, Some(&mut j_xb), None);
+            let r = q.rminus(&p, Some(&mut j_q), Some(&mut j_p)).val;
+            let j = j_p * j_ax + j_q * j_xb;
+            h += j.transpose() * j;
+            g += j.transpose() * r;
+        }
+        let delta = -solve_spd(&h, &g).ok_or(HandEyeError::Degenerate)?;
+        x = x.rplus(&Vec6 { val: delta }, None, None);
+        if delta.dot(&delta).sqrt() <= options.tolerance {
+            return Ok(hand_eye(pairs, x, iterations, true));
+        }
+    }
+    Ok(hand_eye(pairs, x, options.max_iterations, false))
+}
+
+fn robot_world<T: Real>(
+    pairs: &[(SE3<T>, SE3<T>)],
+    x: SE3<T>,
+    y: SE3<T>,
+    iterations: usize,
+    converged: bool,
+) -> RobotWorld<T> {
+    let residuals = residuals(pairs, |a| a.mat_mul(&x), |b| y.mat_mul(b));
+    RobotWorld {
+        x,
+        y,
+        residuals,
+        iterations,
+        converged,
+    }
+}
+
+/// `AX = YB` in closed form, in the manner of Shah
+///
+/// the rotations are the null vector of the stacked Kronecker products of `R_A R_X = R_Y R_B`,
+/// projected onto SO3, the translations are then linear.
+/// requires at least 3 pairs with non parallel rotation axes
+pub fn ax_yb<T: Real + RealField>(
+    pairs: &[(SE3<T>, SE3<T>)],
+) -> Result<RobotWorld<T>, HandEyeError> {
+    if pairs.len() < 3 {
+        return Err(HandEyeError::TooFewPairs);
+    }
+    let id = Matrix3::identity();
+    let mut n = SMatrix::<T, 18, 18>::zeros();
+    for (a, b) in pairs {
+        let (ra, _) = a.rp();
+        let (rb, _) = b.rp();
+        // vec(R_A R_X) = (I ⊗ R_A) vec(R_X), vec(R_Y R_B) = (R_Bᵀ ⊗ I) vec(R_Y)
+        let mut k = SMatrix::<T, 9, 18>::zeros();
+        k.fixed_view_mut::<9, 9>(0, 0).copy_from(&id.kronecker(&ra));
+        k.fixed_view_mut::<9, 9>(0, 9)
+            .copy_from(&-rb.transpose().kronecker(&id));
+        n += k.transpose() * k;
+    }
+    let eigen = SymmetricEigen::new(n);
+    let mut order: [usize; 18] = core::array::from_fn(|i| i);
+    order.sort_by(|&i, &j| {
+        eigen.eigenvalues[i]
+            .partial_cmp(&eigen.eigenvalues[j])
+            .unwrap_or(core::cmp::Ordering::Equal)
+    });
+    // the null space must be one dimensional
+    let (l1, l_max) = (eigen.eigenvalues[order[1]], eigen.eigenvalues[order[17]]);
+    if l1 <= l_max * tolerance::<T>() {
+        return Err(HandEyeError::Degenerate);
+    }
+    let v: SVector<T, 18> = eigen.eigenvectors.column(order[0]).into();
+    let rx = Matrix3::from_column_slice(&v.as_slice()[..9]);
+    let ry = Matrix3::from_column_slice(&v.as_slice()[9..]);
+    // the null vector is defined up to its sign
+    let sign = if rx.determinant() < T::zero() {
+        -T::one()
+    } else {
+        T::one()
+    };
+    let project = |m: Matrix3<T>| svd_rotation(&(m * sign).transpose()).map(|(r, _)| r);
+    let rx = project(rx).map_err(|_| HandEyeError::Degenerate)?;
+    let ry = project(ry).map_err(|_| HandEyeError::Degenerate)?;
+
+    // [R_A, -I] [t_X; t_Y] = R_Y t_B - t_A
+    let mut h = SMatrix::<T, 6, 6>::zeros();
+    let mut g = Vector6::zeros();
+    for (a, b) in pairs {
+        let (ra, ta) = a.rp();
+        let (_, tb) = b.rp();
+        let mut c = SMatrix::<T, 3, 6>::zeros();
+        c.fixed_view_mut::<3, 3>(0, 0).copy_from(&ra);
+        c.fixed_view_mut::<3, 3>(0, 3).copy_from(&-id);
+        h += c.transpose() * c;
+        g += c.transpose() * (ry * tb - ta);
+    }
+    let t = solve_spd(&h, &g).ok_or(HandEyeError::Degenerate)?;
+    let x = SE3::from_rp(&rx, &t.fixed_rows::<3>(0).into());
+    let y = SE3::from_rp(&ry, &t.fixed_rows::<3>(3).into());
+    Ok(robot_world(pairs, x, y, 0, true))
+}
+
+/// `AX = YB` by Gauss-Newton, minimizing `Σ |log((AᵢX)⁻¹ Y Bᵢ)|²` from initial guesses
+pub fn refine_ax_yb<T: Real>(
+    pairs: &[(SE3<T>, SE3<T>)],
+    initial_x: &SE3<T>,
+    initial_y: &SE3<T>,
+    options: &RefineOptions<T>,
+) -> Result<RobotWorld<T>, HandEyeError> {
+    if pairs.len() < 3 {
+        return Err(HandEyeError::TooFewPairs);
+    }
+    let (mut x, mut y) = (initial_x.clone(), initial_y.clone());
+    for iterations in 1..=options.max_iterations {
+        let mut h = SMatrix::<T, 12, 12>::zeros();
+        let mut g = SVector::<T, 12>::zeros();
+        for (a, b) in pairs {
+            let (mut j_ax, mut j_yb) = (SMatrix::zeros(), SMatrix::zeros());
+            let (mut j_p, mut j_q) = (SMatrix::zeros(), SMatrix::zeros());
+            let p = a.compose(&x, None, Some(&mut j_ax));
+            let q = y.compose(b, Some(&mut j_yb), None);
+            let r = q.rminus(&p, Some(&mut j_q), Some(&mut j_p)).val;
+            let mut j = SMatrix::<T, 6, 12>::zeros();
+            j.fixed_view_mut::<6, 6>(0, 0).copy_from(&(j_p * j_ax));
+            j.fixed_view_mut::<6, 6>(0, 6).copy_from(&(j_q * j_yb));
+            h += j.transpose() * j;
+            g += j.transpose() * r;
+        }
+        let delta = -solve_spd(&h, &g).ok_or(HandEyeError::Degenerate)?;
+        x = x.rplus(
+            &Vec6 {
+                val: delta.fixed_rows::<6>(0).into(),
+            },
+            None,
+            None,
+        );
+        y = y.rplus(
+            &Vec6 {
+                val: delta.fixed_rows::<6>(6).into(),
+            },
+            None,
+            None,
+        );
+        if delta.dot(&delta).sqrt() <= options.tolerance {
+            return Ok(robot_world(pairs, x, y, iterations, true));
+        }
+    }
+    Ok(robot_world(pairs, x, y, options.max_iterations, false))
+}
+
+fn tolerance<T: Real>() -> T {
+    T::epsilon().sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn pose(rpy: [f64; 3], p: [f64; 3]) -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(rpy[0], rpy[1], rpy[2]), p)
+    }
+
+    fn motions() -> Vec<SE3<f64>> {
+        vec![
+            pose([0.3, 0., 0.1], [0.1, 0.2, 0.]),
+            pose([0., 0.4, -0.2], [-0.3, 0., 0.2]),
+            pose([0.2, -0.3, 0.5], [0., 0.1, -0.4]),
+            pose([-0.6, 0.1, 0.2], [0.5, -0.2, 0.1]),
+            pose([0.1, 0.7, -0.4], [0.2, 0.3, 0.3]),
+        ]
+    }
+
+    fn x() -> SE3<f64> {
+        pose([0.5, -0.4, 1.2], [0.05, -0.1, 0.2])
+    }
+
+    fn y() -> SE3<f64> {
+        pose([-1., 0.3, 2.], [1., 0.5, -0.3])
+    }
+
+    fn ax_xb_pairs() -> Vec<(SE3<f64>, SE3<f64>)> {
+        motions()
+            .into_iter()
+            .map(|a| {
+                let b = x().inv().mat_mul(&a).mat_mul(&x());
+                (a, b)
+            })
+            .collect()
+    }
+
+    fn ax_yb_pairs() -> Vec<(SE3<f64>, SE3<f64>)> {
+        motions()
+            .into_iter()
+            .map(|a| {
+                let b = y().inv().mat_mul(&a).mat_mul(&x());
+                (a, b)
+            })
+            .collect()
+    }
+
+    /// perturb the second element of the pairs
+    fn noisy(pairs: &[(SE3<f64>, SE3<f64>)]) -> Vec<(SE3<f64>, SE3<f64>)> {
+        pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (a, b))| {
+                let s = if i % 2 == 0 { 1e-3 } else { -1e-3 };
+                let noise = Vec6::new([s, -s, 2. * s], [s, s, -s]);
+                (a.clone(), b.plus(&noise))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ax_xb() {
+        let pairs = ax_xb_pairs();
+        for calib in [tsai_lenz(&pairs).unwrap(), park_martin(&pairs).unwrap()] {
+            assert_relative_eq!(calib.x, x(), epsilon = 1e-10);
+            assert!(calib.residuals.rotation_max < 1e-10);
+            assert!(calib.residuals.translation_max < 1e-10);
+        }
+        let initial = x().plus(&Vec6::new([0.1, -0.1, 0.05], [0.05, 0., -0.05]));
+        let calib = refine_ax_xb(&pairs, &initial, &RefineOptions::default()).unwrap();
+        assert!(calib.converged);
+        assert_relative_eq!(calib.x, x(), epsilon = 1e-10);
+
+        // refinement from a closed form solution lowers the residuals of noisy pairs
+        let pairs = noisy(&pairs);
+        let park = park_martin(&pairs).unwrap();
+        let tsai = tsai_lenz(&pairs).unwrap();
+        assert_relative_eq!(park.x, x(), epsilon = 1e-2);
+        assert_relative_eq!(tsai.x, x(), epsilon = 1e-2);
+        let refined = refine_ax_xb(&pairs, &park.x, &RefineOptions::default()).unwrap();
+        assert!(refined.converged);
+        let cost = |r: &Residuals<f64>| r.rotation_rms.powi(2) + r.translation_rms.powi(2);
+        assert!(cost(&refined.residuals) <= cost(&park.residuals));
+    }
+
+    #[test]
+    fn test_ax_yb() {
+        let pairs = ax_yb_pairs();
+        let calib = ax_yb(&pairs).unwrap();
+        assert_relative_eq!(calib.x, x(), epsilon = 1e-9);
+        assert_relative_eq!(calib.y, y(), epsilon = 1e-9);
+        assert!(calib.residuals.rotation_max < 1e-9);
+
+        let pairs = noisy(&pairs);
+        let calib = ax_yb(&pairs).unwrap();
+        assert_relative_eq!(calib.x, x(), epsilon = 1e-2);
+        let refined = refine_ax_yb(&pairs, &calib.x, &calib.y, &RefineOptions::default()).unwrap();
+        assert!(refined.converged);
+        assert_relative_eq!(refined.x, x(), epsilon = 1e-2);
+        assert_relative_eq!(refined.y, y(), epsilon = 1e-2);
+        let cost = |r: &Residuals<f64>| r.rotation_rms.powi(2) + r.translation_rms.powi(2);
+        assert!(cost(&refined.residuals) <= cost(&calib.residuals));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        // rotations about a single axis leave the translation along it undetermined
+        let pairs: Vec<_> = [0.3, -0.5, 0.8]
+            .into_iter()
+            .map(|angle| {
+                let a = pose([0., 0., angle], [angle, 0.1, 0.]);
+                (a.clone(), x().inv().mat_mul(&a).mat_mul(&x()))
+            })
+            .collect();
+        assert_eq!(tsai_lenz(&pairs), Err(HandEyeError::Degenerate));
+        assert_eq!(park_martin(&pairs), Err(HandEyeError::Degenerate));
+        assert_eq!(
+            refine_ax_xb(&pairs, &x(), &RefineOptions::default()),
+            Err(HandEyeError::Degenerate)
+        );
+        assert_eq!(park_martin(&pairs[..1]), Err(HandEyeError::TooFewPairs));
+        assert_eq!(ax_yb(&pairs[..2]), Err(HandEyeError::TooFewPairs));
+    }
+}
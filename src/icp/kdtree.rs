@@ -0,0 +1,108 @@
+use core::cmp::Ordering;
+
+use alloc::vec::Vec;
+use nalgebra::Vector3;
+
+use crate::{Point, Real};
+
+/// kd-tree over a borrowed point set, for nearest neighbour queries
+#[derive(Debug, Clone)]
+pub struct KdTree<'a, T> {
+    points: &'a [Point<T>],
+    // implicit balanced tree, the median of each range is its root,
+    // ranges at depth d are split along axis d % 3
+    index: Vec<usize>,
+}
+
+impl<'a, T: Real> KdTree<'a, T> {
+    /// build the tree in `O(n log n)`
+    pub fn new(points: &'a [Point<T>]) -> Self {
+        let mut index: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut index, 0);
+        Self { points, index }
+    }
+
+    /// index and squared distance of the nearest point, `None` if the tree is empty
+    pub fn nearest(&self, p: &Point<T>) -> Option<(usize, T)> {
+        let mut best = None;
+        self.search(&self.index, 0, &p.val, &mut best);
+        best
+    }
+
+    fn search(&self, index: &[usize], depth: usize, q: &Vector3<T>, best: &mut Option<(usize, T)>) {
+        if index.is_empty() {
+            return;
+        }
+        let mid = index.len() / 2;
+        let i = index[mid];
+        let p = &self.points[i].val;
+        let d = p - q;
+        let d2 = d.dot(&d);
+        if best.is_none_or(|(_, b)| d2 < b) {
+            *best = Some((i, d2));
+        }
+        let axis = depth % 3;
+        let diff = q[axis] - p[axis];
+        let (near, far) = if diff < T::zero() {
+            (&index[..mid], &index[mid + 1..])
+        } else {
+            (&index[mid + 1..], &index[..mid])
+        };
+        self.search(near, depth + 1, q, best);
+        // the far side can only hold a closer point if the splitting plane is closer
+        if best.is_none_or(|(_, b)| diff * diff < b) {
+            self.search(far, depth + 1, q, best);
+        }
+    }
+}
+
+fn build<T: Real>(points: &[Point<T>], index: &mut [usize], depth: usize) {
+    if index.len() <= 1 {
+        return;
+    }
+    let axis = depth % 3;
+    let mid = index.len() / 2;
+    index.select_nth_unstable_by(mid, |&a, &b| {
+        points[a].val[axis]
+            .partial_cmp(&points[b].val[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+    let (left, right) = index.split_at_mut(mid);
+    build(points, left, depth + 1);
+    build(points, &mut right[1..], depth + 1);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_nearest() {
+        // deterministic pseudo random points
+        let mut state = 1u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 * 2. - 1.
+        };
+        let points: Vec<_> = (0..500)
+            .map(|_| Point::new(next(), next(), next()))
+            .collect();
+        let tree = KdTree::new(&points);
+        for _ in 0..200 {
+            let q = Point::new(next() * 1.5, next() * 1.5, next() * 1.5);
+            let brute = points
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (i, (p.val - q.val).norm_squared()))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            assert_eq!(tree.nearest(&q), Some(brute));
+        }
+        assert_eq!(
+            KdTree::<f64>::new(&[]).nearest(&Point::new(0., 0., 0.)),
+            None
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Iterative closest point registration, requires the `alloc` feature
+//!
+//! each iteration matches the transformed source points to their nearest target points,
+//! solves the reweighted Gauss-Newton step `δ` linearized in se3 and updates the pose
+//! on the left, `T = exp(δ^) * T`
+//!
+//! ```rust
+//! use liealg::icp::{point_to_point, IcpOptions};
+//! use liealg::prelude::*;
+//! use liealg::{Point, SE3, SO3};
+//!
+//! let target: Vec<_> = (0..100)
+//!     .map(|i| {
+//!         let t = i as f64 * 0.1;
+//!         Point::new(t.cos(), t.sin(), 0.1 * t * t)
+//!     })
+//!     .collect();
+//! let pose = SE3::new(&SO3::from_euler_angles(0.02, -0.01, 0.03), [0.01, 0.02, -0.01]);
+//! let source: Vec<_> = target.iter().map(|p| pose.inv().act(p)).collect();
+//! let result = point_to_point(&source, &target, &SE3::identity(), &IcpOptions::default()).unwrap();
+//! assert!(result.converged);
+//! ```
+
+mod kdtree;
+
+use core::fmt::Display;
+
+use alloc::vec::Vec;
+use nalgebra::{Matrix1x6, Matrix3x6, Matrix6, Vector3, Vector6};
+
+use crate::{
+    robust::Kernel,
+    utils::{hat, solve_spd},
+    Algebra, Group, Point, Real, Vec6, Vector, SE3,
+};
+
+pub use kdtree::KdTree;
+
+/// nearest neighbour search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbors {
+    /// compare with every target point, `O(n m)` per iteration
+    BruteForce,
+    /// search a kd-tree built once over the target points
+    KdTree,
+}
+
+/// options of the ICP
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IcpOptions<T> {
+    /// maximum number of iterations
+    pub max_iterations: usize,
+    /// the iteration stops once the norm of the se3 update is below the tolerance
+    pub tolerance: T,
+    /// correspondences farther apart are rejected
+    pub max_distance: T,
+    /// robust kernel of the residuals
+    pub kernel: Kernel<T>,
+    /// nearest neighbour search
+    pub neighbors: Neighbors,
+}
+
+impl<T: Real> Default for IcpOptions<T> {
+    fn default() -> Self {
+        Self {
+            max_iterations: 50,
+            tolerance: T::epsilon().sqrt(),
+            max_distance: T::max_value(),
+            kernel: Kernel::L2,
+            neighbors: Neighbors::KdTree,
+        }
+    }
+}
+
+/// result of the ICP
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcpResult<T> {
+    /// pose mapping the source points onto the target points
+    pub pose: SE3<T>,
+    /// number of iterations performed
+    pub iterations: usize,
+    /// whether the last update is below the tolerance
+    pub converged: bool,
+    /// root mean square of the residuals of the last correspondences
+    pub rmse: T,
+    /// number of the last correspondences
+    pub correspondences: usize,
+}
+
+/// error of the ICP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcpError {
+    /// the number of normals differs from the number of target points
+    LengthMismatch,
+    /// too few correspondences within the maximum distance to constrain the pose
+    TooFewCorrespondences,
+    /// the linearized system is singular, e.g. point-to-plane on a single plane
+    Degenerate,
+}
+
+impl Display for IcpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IcpError::LengthMismatch => write!(f, "number of normals and target points differ"),
+            IcpError::TooFewCorrespondences => write!(f, "too few correspondences"),
+            IcpError::Degenerate => write!(f, "degenerate configuration"),
+        }
+    }
+}
+
+/// point-to-point ICP, minimizing `Σ ρ(|T sᵢ - tᵢ|)`
+pub fn point_to_point<T: Real>(
+    source: &[Point<T>],
+    target: &[Point<T>],
+    initial: &SE3<T>,
+    options: &IcpOptions<T>,
+) -> Result<IcpResult<T>, IcpError> {
+    icp(source, target, initial, options, 3, |q, j| {
+        // ∂(exp(δ^) q) / ∂δ = [-q^, I]
+        let mut jac = Matrix3x6::zeros();
+        jac.fixed_view_mut::<3, 3>(0, 0).copy_from(&-hat(q));
+        jac.fixed_view_mut::<3, 3>(0, 3)
+            .copy_from(&nalgebra::Matrix3::identity());
+        let r = q - target[j].val;
+        let norm = r.dot(&r).sqrt();
+        (jac.transpose() * jac, jac.transpose() * r, norm)
+    })
+}
+
+/// point-to-plane ICP, minimizing `Σ ρ(|nᵢᵀ (T sᵢ - tᵢ)|)` with the unit normals `nᵢ` of the target points
+pub fn point_to_plane<T: Real>(
+    source: &[Point<T>],
+    target: &[Point<T>],
+    normals: &[[T; 3]],
+    initial: &SE3<T>,
+    options: &IcpOptions<T>,
+) -> Result<IcpResult<T>, IcpError> {
+    if normals.len() != target.len() {
+        return Err(IcpError::LengthMismatch);
+    }
+    icp(source, target, initial, options, 6, |q, j| {
+        let n = Vector3::from(normals[j]);
+        let mut jac = Matrix1x6::zeros();
+        jac.fixed_view_mut::<1, 3>(0, 0)
+            .copy_from(&q.cross(&n).transpose());
+        jac.fixed_view_mut::<1, 3>(0, 3).copy_from(&n.transpose());
+        let r = n.dot(&(q - target[j].val));
+        (jac.transpose() * jac, jac.transpose() * r, r.abs())
+    })
+}
+
+/// the residual function returns `JᵀJ`, `Jᵀr` and `|r|` of a correspondence
+fn icp<T: Real>(
+    source: &[Point<T>],
+    target: &[Point<T>],
+    initial: &SE3<T>,
+    options: &IcpOptions<T>,
+    min_correspondences: usize,
+    residual: impl Fn(&Vector3<T>, usize) -> (Matrix6<T>, Vector6<T>, T),
+) -> Result<IcpResult<T>, IcpError> {
+    let tree = match options.neighbors {
+        Neighbors::KdTree => Some(KdTree::new(target)),
+        Neighbors::BruteForce => None,
+    };
+    let nearest = |p: &Point<T>| match &tree {
+        Some(tree) => tree.nearest(p),
+        None => brute_force(target, p),
+    };
+    let max_d2 = if options.max_distance < T::max_value().sqrt() {
+        options.max_distance * options.max_distance
+    } else {
+        T::max_value()
+    };
+
+    let mut pose = initial.clone();
+    let mut matches = Vec::with_capacity(source.len());
+    let mut result = IcpResult {
+        pose: pose.clone(),
+        iterations: 0,
+        converged: false,
+        rmse: T::zero(),
+        correspondences: 0,
+    };
+    for iterations in 1..=options.max_iterations {
+        matches.clear();
+        for s in source {
+            let q = pose.act(s);
+            if let Some((j, d2)) = nearest(&q) {
+                if d2 <= max_d2 {
+                    matches.push((q.val, j));
+                }
+            }
+        }
+        if matches.len() < min_correspondences {
+            return Err(IcpError::TooFewCorrespondences);
+        }
+
+        let mut h = Matrix6::zeros();
+        let mut g = Vector6::zeros();
+        let mut sum2 = T::zero();
+        for (q, j) in &matches {
+            let (jtj, jtr, r) = residual(q, *j);
+            let w = options.kernel.weight(r);
+            h += jtj * w;
+            g += jtr * w;
+            sum2 += r * r;
+        }
+        let delta = -solve_spd(&h, &g).ok_or(IcpError::Degenerate)?;
+        pose = Vec6 { val: delta }.hat().exp().mat_mul(&pose);
+
+        let step = delta.dot(&delta).sqrt();
+        result = IcpResult {
+            pose: pose.clone(),
+            iterations,
+            converged: step <= options.tolerance,
+            rmse: (sum2 / T::from(matches.len()).unwrap()).sqrt(),
+            correspondences: matches.len(),
+        };
+        if result.converged {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+fn brute_force<T: Real>(target: &[Point<T>], p: &Point<T>) -> Option<(usize, T)> {
+    let mut best: Option<(usize, T)> = None;
+    for (i, t) in target.iter().enumerate() {
+        let d = t.val - p.val;
+        let d2 = d.dot(&d);
+        if best.is_none_or(|(_, b)| d2 < b) {
+            best = Some((i, d2));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::SO3;
+
+    use super::*;
+
+    /// points on three faces of a box, with their normals
+    fn scene() -> (Vec<Point<f64>>, Vec<[f64; 3]>) {
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        for i in 0..15 {
+            for j in 0..15 {
+                let (u, v) = (
+                    i as f64 * 0.1,
+                    j as f64 * 0.1 + 0.05 * (i as f64 * 0.7).sin(),
+                );
+                points.push(Point::new(u, v, 0.));
+                normals.push([0., 0., 1.]);
+                points.push(Point::new(u, 0., v + 0.03));
+                normals.push([0., 1., 0.]);
+                points.push(Point::new(0., u + 0.02, v));
+                normals.push([1., 0., 0.]);
+            }
+        }
+        (points, normals)
+    }
+
+    fn pose() -> SE3<f64> {
+        SE3::new(
+            &SO3::from_euler_angles(0.05, -0.03, 0.08),
+            [0.04, -0.02, 0.03],
+        )
+    }
+
+    #[test]
+    fn test_point_to_point() {
+        let (target, _) = scene();
+        let source: Vec<_> = target.iter().map(|p| pose().inv().act(p)).collect();
+        for neighbors in [Neighbors::BruteForce, Neighbors::KdTree] {
+            let options = IcpOptions {
+                neighbors,
+                max_iterations: 100,
+                ..Default::default()
+            };
+            let result = point_to_point(&source, &target, &SE3::identity(), &options).unwrap();
+            assert!(result.converged);
+            assert_relative_eq!(result.pose, pose(), epsilon = 1e-6);
+            assert!(result.rmse < 1e-6);
+            assert_eq!(result.correspondences, source.len());
+        }
+    }
+
+    #[test]
+    fn test_point_to_plane() {
+        let (target, normals) = scene();
+        let source: Vec<_> = target.iter().map(|p| pose().inv().act(p)).collect();
+        let result = point_to_plane(
+            &source,
+            &target,
+            &normals,
+            &SE3::identity(),
+            &IcpOptions::default(),
+        )
+        .unwrap();
+        assert!(result.converged);
+        assert!(result.iterations < 20);
+        assert_relative_eq!(result.pose, pose(), epsilon = 1e-6);
+
+        // a single plane leaves the in-plane motion unconstrained
+        let plane: Vec<_> = target.iter().step_by(3).cloned().collect();
+        let normals = vec![[0., 0., 1.]; plane.len()];
+        let result = point_to_plane(
+            &plane,
+            &plane,
+            &normals,
+            &SE3::identity(),
+            &IcpOptions::default(),
+        );
+        assert_eq!(result, Err(IcpError::Degenerate));
+        assert_eq!(
+            point_to_plane(
+                &plane,
+                &plane,
+                &normals[1..],
+                &SE3::identity(),
+                &IcpOptions::default()
+            ),
+            Err(IcpError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn test_robust() {
+        let (target, _) = scene();
+        let mut source: Vec<_> = target.iter().map(|p| pose().inv().act(p)).collect();
+        // gross outliers
+        for p in source.iter_mut().step_by(10) {
+            *p = Point::new(p.x() + 0.3, p.y() - 0.2, p.z() + 0.25);
+        }
+        let l2 =
+            point_to_point(&source, &target, &SE3::identity(), &IcpOptions::default()).unwrap();
+        for kernel in [Kernel::Huber(0.01), Kernel::Cauchy(0.01)] {
+            let options = IcpOptions {
+                kernel,
+                max_iterations: 200,
+                ..Default::default()
+            };
+            let result = point_to_point(&source, &target, &SE3::identity(), &options).unwrap();
+            let err = result.pose.minus(&pose()).as_array().map(f64::abs);
+            let err_l2 = l2.pose.minus(&pose()).as_array().map(f64::abs);
+            let (err, err_l2) = (
+                err.iter().cloned().fold(0., f64::max),
+                err_l2.iter().cloned().fold(0., f64::max),
+            );
+            assert!(err < err_l2 * 0.5, "{kernel:?} {err} {err_l2}");
+        }
+
+        let options = IcpOptions {
+            max_distance: 1e-3,
+            ..Default::default()
+        };
+        let far: Vec<_> = source
+            .iter()
+            .map(|p| Point::new(p.x() + 5., p.y(), p.z()))
+            .collect();
+        assert_eq!(
+            point_to_point(&far, &target, &SE3::identity(), &options),
+            Err(IcpError::TooFewCorrespondences)
+        );
+    }
+}
@@ -48,9 +48,49 @@ macro_rules! impl_approx {
 impl_approx!(
     Point<T>
     AdjSO3<T> so3<T> SO3<T> Vec3<T>
-    AdjSE3<T> se3<T> SE3<T> Vec6<T>
+    AdjSE3<T> se3<T> Vec6<T>
 );
 
+impl<T: PartialEq> PartialEq for SE3<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rot == other.rot && self.trans == other.trans
+    }
+}
+
+impl<T: AbsDiffEq + Real> AbsDiffEq for SE3<T>
+where
+    T::Epsilon: Copy,
+{
+    type Epsilon = T::Epsilon;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        other.rot.abs_diff_eq(&self.rot, epsilon) && other.trans.abs_diff_eq(&self.trans, epsilon)
+    }
+}
+
+impl<T: RelativeEq + Real> RelativeEq for SE3<T>
+where
+    T::Epsilon: Copy,
+{
+    fn default_max_relative() -> Self::Epsilon {
+        T::default_max_relative()
+    }
+
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool {
+        other.rot.relative_eq(&self.rot, epsilon, max_relative)
+            && other.trans.relative_eq(&self.trans, epsilon, max_relative)
+    }
+}
+
 #[test]
 fn test_approx() {
     use approx::assert_relative_eq;
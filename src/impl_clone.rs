@@ -17,9 +17,18 @@ macro_rules! impl_clone {
 impl_clone!(
     Point<T>
     AdjSO3<T> so3<T> SO3<T> Vec3<T>
-    AdjSE3<T> se3<T> SE3<T> Vec6<T>
+    AdjSE3<T> se3<T> Vec6<T>
 );
 
+impl<T: Clone> Clone for SE3<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rot: self.rot.clone(),
+            trans: self.trans.clone(),
+        }
+    }
+}
+
 #[test]
 fn clone_test() {
     use crate::prelude::*;
@@ -0,0 +1,136 @@
+use nalgebra::{Matrix3, Matrix4, Vector3};
+
+use crate::{Point, Real, Vec3, SE3, SO3};
+
+impl<T: Real> From<SO3<T>> for mint::RowMatrix3<T> {
+    fn from(rot: SO3<T>) -> Self {
+        let r = rot.val;
+        mint::RowMatrix3 {
+            x: [r[(0, 0)], r[(0, 1)], r[(0, 2)]].into(),
+            y: [r[(1, 0)], r[(1, 1)], r[(1, 2)]].into(),
+            z: [r[(2, 0)], r[(2, 1)], r[(2, 2)]].into(),
+        }
+    }
+}
+
+/// the matrix is not checked to be a valid rotation matrix
+impl<T: Real> From<mint::RowMatrix3<T>> for SO3<T> {
+    fn from(m: mint::RowMatrix3<T>) -> Self {
+        SO3 {
+            val: Matrix3::new(
+                m.x.x, m.x.y, m.x.z, m.y.x, m.y.y, m.y.z, m.z.x, m.z.y, m.z.z,
+            ),
+        }
+    }
+}
+
+impl<T: Real> From<SO3<T>> for mint::Quaternion<T> {
+    fn from(rot: SO3<T>) -> Self {
+        let [x, y, z, w] = rot.quaternion();
+        mint::Quaternion {
+            v: [x, y, z].into(),
+            s: w,
+        }
+    }
+}
+
+/// the quaternion is normalized before conversion
+impl<T: Real> From<mint::Quaternion<T>> for SO3<T> {
+    fn from(q: mint::Quaternion<T>) -> Self {
+        SO3::from_quaternion([q.v.x, q.v.y, q.v.z, q.s])
+    }
+}
+
+impl<T: Real> From<SE3<T>> for mint::ColumnMatrix4<T> {
+    fn from(t: SE3<T>) -> Self {
+        let m = t.matrix();
+        mint::ColumnMatrix4 {
+            x: [m[(0, 0)], m[(1, 0)], m[(2, 0)], m[(3, 0)]].into(),
+            y: [m[(0, 1)], m[(1, 1)], m[(2, 1)], m[(3, 1)]].into(),
+            z: [m[(0, 2)], m[(1, 2)], m[(2, 2)], m[(3, 2)]].into(),
+            w: [m[(0, 3)], m[(1, 3)], m[(2, 3)], m[(3, 3)]].into(),
+        }
+    }
+}
+
+/// the rotation block is not checked to be a valid rotation matrix,
+/// the last row is always set to `[0, 0, 0, 1]`
+impl<T: Real> From<mint::ColumnMatrix4<T>> for SE3<T> {
+    fn from(m: mint::ColumnMatrix4<T>) -> Self {
+        let m: Matrix4<T> = Matrix4::from_columns(&[
+            [m.x.x, m.x.y, m.x.z, m.x.w].into(),
+            [m.y.x, m.y.y, m.y.z, m.y.w].into(),
+            [m.z.x, m.z.y, m.z.z, m.z.w].into(),
+            [m.w.x, m.w.y, m.w.z, m.w.w].into(),
+        ]);
+        SE3::from_rp(
+            &m.fixed_view::<3, 3>(0, 0).into(),
+            &m.fixed_view::<3, 1>(0, 3).into(),
+        )
+    }
+}
+
+impl<T: Real> From<Point<T>> for mint::Point3<T> {
+    fn from(p: Point<T>) -> Self {
+        [p.val.x, p.val.y, p.val.z].into()
+    }
+}
+
+impl<T: Real> From<mint::Point3<T>> for Point<T> {
+    fn from(p: mint::Point3<T>) -> Self {
+        Point::new(p.x, p.y, p.z)
+    }
+}
+
+impl<T: Real> From<Vec3<T>> for mint::Vector3<T> {
+    fn from(v: Vec3<T>) -> Self {
+        [v.val.x, v.val.y, v.val.z].into()
+    }
+}
+
+impl<T: Real> From<mint::Vector3<T>> for Vec3<T> {
+    fn from(v: mint::Vector3<T>) -> Self {
+        Vec3 {
+            val: Vector3::new(v.x, v.y, v.z),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_so3_matrix() {
+        let rot = SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3);
+        let m: mint::RowMatrix3<f64> = rot.clone().into();
+        assert_eq!(m.x.y, rot.val[(0, 1)]);
+        assert_eq!(SO3::from(m), rot);
+    }
+
+    #[test]
+    fn test_so3_quaternion() {
+        let rot = SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3);
+        let q: mint::Quaternion<f64> = rot.clone().into();
+        assert_relative_eq!(SO3::from(q), rot, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_se3_matrix() {
+        let t = SE3::new(&SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3), [1., 2., 3.]);
+        let m: mint::ColumnMatrix4<f64> = t.clone().into();
+        assert_eq!(m.w.x, 1.);
+        assert_eq!(m.x.w, 0.);
+        assert_eq!(SE3::from(m), t);
+    }
+
+    #[test]
+    fn test_point_vector() {
+        let p: mint::Point3<f64> = Point::new(1., 2., 3.).into();
+        assert_eq!(Point::from(p), Point::new(1., 2., 3.));
+        let v: mint::Vector3<f64> = Vec3::new(1., 2., 3.).into();
+        assert_eq!(Vec3::from(v), Vec3::new(1., 2., 3.));
+    }
+}
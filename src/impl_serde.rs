@@ -0,0 +1,243 @@
+use nalgebra::{Matrix3, Vector3, Vector6};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{se3, so3, AdjSE3, AdjSO3, Group, Point, Real, Vec3, Vec6, SE3, SO3};
+
+/// maximum deviation of a deserialized quaternion norm from 1
+const QUATERNION_TOLERANCE: f64 = 1e-3;
+
+/// SE3 representation: unit quaternion `[x, y, z, w]` and translation `[x, y, z]`
+#[derive(Serialize, Deserialize)]
+struct Pose<T> {
+    rotation: [T; 4],
+    translation: [T; 3],
+}
+
+fn check_quaternion<T: Real, E: Error>(q: &[T; 4]) -> Result<(), E> {
+    let norm = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    let tolerance = T::from(QUATERNION_TOLERANCE).ok_or_else(|| E::custom("invalid scalar"))?;
+    // written so that NaN is rejected
+    if (norm - T::one()).abs() <= tolerance {
+        Ok(())
+    } else {
+        Err(E::custom(
+            "invalid rotation: quaternion is not a unit quaternion",
+        ))
+    }
+}
+
+fn check_finite<T: Real, E: Error>(v: &[T]) -> Result<(), E> {
+    if v.iter().all(|x| x.abs() < T::max_value()) {
+        Ok(())
+    } else {
+        Err(E::custom("invalid value: not finite"))
+    }
+}
+
+fn serialize_pose<T, S>(r: &Matrix3<T>, p: &Vector3<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Real + Serialize,
+    S: Serializer,
+{
+    Pose {
+        rotation: SO3 { val: *r }.quaternion(),
+        translation: [p.x, p.y, p.z],
+    }
+    .serialize(serializer)
+}
+
+fn deserialize_pose<'de, T, D>(deserializer: D) -> Result<SE3<T>, D::Error>
+where
+    T: Real + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let pose = Pose::<T>::deserialize(deserializer)?;
+    check_quaternion(&pose.rotation)?;
+    check_finite(&pose.translation)?;
+    Ok(SE3::new(
+        &SO3::from_quaternion(pose.rotation),
+        pose.translation,
+    ))
+}
+
+macro_rules! impl_serde_vector3 {
+    ($($t:ident)*) => {
+        $(
+            /// serialized as `[x, y, z]`
+            impl<T: Real + Serialize> Serialize for $t<T> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    [self.val.x, self.val.y, self.val.z].serialize(serializer)
+                }
+            }
+
+            impl<'de, T: Real + Deserialize<'de>> Deserialize<'de> for $t<T> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let v = <[T; 3]>::deserialize(deserializer)?;
+                    check_finite(&v)?;
+                    Ok($t { val: Vector3::from(v) })
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_vector3!(Point Vec3 so3);
+
+macro_rules! impl_serde_vector6 {
+    ($($t:ident)*) => {
+        $(
+            /// serialized as `[wx, wy, wz, x, y, z]`
+            impl<T: Real + Serialize> Serialize for $t<T> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    let v = &self.val;
+                    [v[0], v[1], v[2], v[3], v[4], v[5]].serialize(serializer)
+                }
+            }
+
+            impl<'de, T: Real + Deserialize<'de>> Deserialize<'de> for $t<T> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let v = <[T; 6]>::deserialize(deserializer)?;
+                    check_finite(&v)?;
+                    Ok($t { val: Vector6::from(v) })
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_vector6!(Vec6 se3);
+
+/// serialized as unit quaternion `[x, y, z, w]`
+impl<T: Real + Serialize> Serialize for SO3<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.quaternion().serialize(serializer)
+    }
+}
+
+/// quaternions whose norm is not close to 1 are rejected
+impl<'de, T: Real + Deserialize<'de>> Deserialize<'de> for SO3<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let q = <[T; 4]>::deserialize(deserializer)?;
+        check_quaternion(&q)?;
+        Ok(SO3::from_quaternion(q))
+    }
+}
+
+/// serialized as the rotation it is created from
+impl<T: Real + Serialize> Serialize for AdjSO3<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SO3 { val: self.val }.serialize(serializer)
+    }
+}
+
+impl<'de, T: Real + Deserialize<'de>> Deserialize<'de> for AdjSO3<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(SO3::deserialize(deserializer)?.adjoint())
+    }
+}
+
+/// serialized as `{ "rotation": [x, y, z, w], "translation": [x, y, z] }`
+impl<T: Real + Serialize> Serialize for SE3<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (r, p) = self.rp();
+        serialize_pose(&r, &p, serializer)
+    }
+}
+
+/// quaternions whose norm is not close to 1 are rejected
+impl<'de, T: Real + Deserialize<'de>> Deserialize<'de> for SE3<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_pose(deserializer)
+    }
+}
+
+/// serialized as the rigid body motion it is created from
+impl<T: Real + Serialize> Serialize for AdjSE3<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let r: Matrix3<T> = self.val.fixed_view::<3, 3>(0, 0).into();
+        let p_so3 = self.val.fixed_view::<3, 3>(3, 0) * r.transpose();
+        let p = Vector3::new(p_so3[(2, 1)], p_so3[(0, 2)], p_so3[(1, 0)]);
+        serialize_pose(&r, &p, serializer)
+    }
+}
+
+impl<'de, T: Real + Deserialize<'de>> Deserialize<'de> for AdjSE3<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(deserialize_pose::<T, D>(deserializer)?.adjoint())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::Vector;
+
+    #[test]
+    fn test_vector() {
+        let v = Vec3::new(1., 2., 3.);
+        let s = serde_json::to_string(&v).unwrap();
+        assert_eq!(s, "[1.0,2.0,3.0]");
+        assert_eq!(serde_json::from_str::<Vec3<f64>>(&s).unwrap(), v);
+
+        let v = Vec6::new([1., 2., 3.], [4., 5., 6.]).hat();
+        let s = serde_json::to_string(&v).unwrap();
+        assert_eq!(s, "[1.0,2.0,3.0,4.0,5.0,6.0]");
+        assert_eq!(serde_json::from_str::<se3<f64>>(&s).unwrap(), v);
+
+        let p: Point<f32> = serde_json::from_str("[1, 2, 3]").unwrap();
+        assert_eq!(p, Point::new(1., 2., 3.));
+    }
+
+    #[test]
+    fn test_so3() {
+        let rot = SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3);
+        let s = serde_json::to_string(&rot).unwrap();
+        assert_relative_eq!(
+            serde_json::from_str::<SO3<f64>>(&s).unwrap(),
+            rot,
+            epsilon = 1e-12
+        );
+        let adj = rot.adjoint();
+        assert_eq!(serde_json::to_string(&adj).unwrap(), s);
+        assert_relative_eq!(
+            serde_json::from_str::<AdjSO3<f64>>(&s).unwrap(),
+            adj,
+            epsilon = 1e-12
+        );
+
+        let rot: SO3<f64> = serde_json::from_str("[0, 0, 0.7071, 0.7071]").unwrap();
+        assert_relative_eq!(rot.val[(1, 0)], 1., epsilon = 1e-12);
+        assert!(serde_json::from_str::<SO3<f64>>("[0, 0, 1, 1]").is_err());
+        assert!(serde_json::from_str::<SO3<f64>>("[0, 0, 0]").is_err());
+    }
+
+    #[test]
+    fn test_se3() {
+        let t = SE3::new(&SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3), [1., 2., 3.]);
+        let s = serde_json::to_string(&t).unwrap();
+        assert!(s.starts_with("{\"rotation\":["));
+        assert!(s.ends_with("\"translation\":[1.0,2.0,3.0]}"));
+        assert_relative_eq!(
+            serde_json::from_str::<SE3<f64>>(&s).unwrap(),
+            t,
+            epsilon = 1e-12
+        );
+        let adj = t.adjoint();
+        let s_adj = serde_json::to_string(&adj).unwrap();
+        assert_relative_eq!(
+            serde_json::from_str::<SE3<f64>>(&s_adj).unwrap(),
+            t,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            serde_json::from_str::<AdjSE3<f64>>(&s).unwrap(),
+            adj,
+            epsilon = 1e-12
+        );
+
+        let invalid = r#"{"rotation": [0, 0, 0, 2], "translation": [1, 2, 3]}"#;
+        assert!(serde_json::from_str::<SE3<f64>>(invalid).is_err());
+    }
+}
@@ -0,0 +1,492 @@
+use std::{collections::HashMap, io::BufRead, io::Write, vec::Vec};
+
+use nalgebra::Matrix6;
+
+use super::{fields, id, numbers, quaternion, to_f64, ParseError, ReadError};
+use crate::{posegraph::PoseGraph, Real, SE3, SO3};
+
+/// g2o information matrices are ordered `[x, y, z, qx, qy, qz]`, translation first,
+/// `G2O_ORDER[k]` is the index in the `[ω; v]` order of [Vec6](crate::Vec6)
+const G2O_ORDER: [usize; 6] = [3, 4, 5, 0, 1, 2];
+
+/// planar `[x, y, θ]` in the `[ω; v]` order
+const PLANAR_ORDER: [usize; 3] = [3, 4, 2];
+
+/// file format of a pose graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// g2o `VERTEX_SE3:QUAT` and `EDGE_SE3:QUAT`
+    G2oSe3,
+    /// g2o `VERTEX_SE2` and `EDGE_SE2`, the poses are projected on the xy plane
+    G2oSe2,
+    /// TORO `VERTEX2` and `EDGE2`, the poses are projected on the xy plane
+    Toro,
+}
+
+/// pose graph read from a file, with the file ids of its nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset<T> {
+    /// nodes, edges and fixed nodes, `FIX` lines of g2o files fix nodes
+    pub graph: PoseGraph<T>,
+    /// id in the file of each node
+    pub ids: Vec<usize>,
+}
+
+impl<T: Real> Default for Dataset<T> {
+    fn default() -> Self {
+        Self {
+            graph: PoseGraph::new(),
+            ids: Vec::new(),
+        }
+    }
+}
+
+/// read a g2o or TORO pose graph
+///
+/// the 3D tags `VERTEX_SE3:QUAT` and `EDGE_SE3:QUAT`, the planar g2o tags `VERTEX_SE2` and `EDGE_SE2`,
+/// the TORO tags `VERTEX2` and `EDGE2` and the g2o `FIX` lines are read, and may be mixed.
+/// information matrices are reordered to `[ω; v]`. the error of `EDGE_SE3:QUAT` is the vector part
+/// of the quaternion, about half the rotation vector, so the rotation block of its information
+/// is scaled by ¼ and the cross blocks by ½.
+/// planar poses lie in the xy plane, their edges get unit information in the out-of-plane directions.
+/// vertices must be declared before the edges using them.
+pub fn read_g2o<T: Real, R: BufRead>(reader: R) -> Result<Dataset<T>, ReadError> {
+    let mut dataset = Dataset::default();
+    let mut index = HashMap::new();
+    for (i, line) in reader.lines().enumerate() {
+        parse_line(&mut dataset, &mut index, &line?)
+            .map_err(|error| ReadError::Parse { line: i + 1, error })?;
+    }
+    Ok(dataset)
+}
+
+fn parse_line<T: Real>(
+    dataset: &mut Dataset<T>,
+    index: &mut HashMap<usize, usize>,
+    line: &str,
+) -> Result<(), ParseError> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let Some((&tag, rest)) = tokens.split_first() else {
+        return Ok(());
+    };
+    let node = |index: &HashMap<usize, usize>, s: &str| {
+        let id = id(s)?;
+        index.get(&id).copied().ok_or(ParseError::UnknownVertex(id))
+    };
+    match tag {
+        _ if tag.starts_with('#') => {}
+        "VERTEX_SE3:QUAT" => {
+            let f = fields(rest, 8)?;
+            let [x, y, z, qx, qy, qz, qw] = numbers(&f[1..])?;
+            let rot = SO3::from_quaternion(quaternion([qx, qy, qz, qw])?);
+            add_vertex(dataset, index, id(f[0])?, SE3::new(&rot, [x, y, z]))?;
+        }
+        "VERTEX_SE2" | "VERTEX2" => {
+            let f = fields(rest, 4)?;
+            let [x, y, theta] = numbers(&f[1..])?;
+            add_vertex(dataset, index, id(f[0])?, planar(x, y, theta))?;
+        }
+        "EDGE_SE3:QUAT" => {
+            let f = fields(rest, 30)?;
+            let (i, j) = (node(index, f[0])?, node(index, f[1])?);
+            let [x, y, z, qx, qy, qz, qw] = numbers(&f[2..9])?;
+            let rot = SO3::from_quaternion(quaternion([qx, qy, qz, qw])?);
+            let upper: [T; 21] = numbers(&f[9..])?;
+            let information = scale_rotation(&symmetric(&G2O_ORDER, &upper), T::from(0.5).unwrap());
+            add_edge(dataset, i, j, SE3::new(&rot, [x, y, z]), information);
+        }
+        "EDGE_SE2" => {
+            let f = fields(rest, 11)?;
+            let (i, j) = (node(index, f[0])?, node(index, f[1])?);
+            let [x, y, theta] = numbers(&f[2..5])?;
+            let upper: [T; 6] = numbers(&f[5..])?;
+            add_edge(
+                dataset,
+                i,
+                j,
+                planar(x, y, theta),
+                planar_information(&upper),
+            );
+        }
+        "EDGE2" => {
+            let f = fields(rest, 11)?;
+            let (i, j) = (node(index, f[0])?, node(index, f[1])?);
+            let [x, y, theta] = numbers(&f[2..5])?;
+            // TORO orders the information as xx xy yy θθ xθ yθ
+            let [xx, xy, yy, tt, xt, yt]: [T; 6] = numbers(&f[5..])?;
+            let information = planar_information(&[xx, xy, xt, yy, yt, tt]);
+            add_edge(dataset, i, j, planar(x, y, theta), information);
+        }
+        "FIX" => {
+            for s in rest {
+                let i = node(index, s)?;
+                dataset.graph.set_fixed(i, true).unwrap();
+            }
+        }
+        _ => return Err(ParseError::UnknownTag(tag.into())),
+    }
+    Ok(())
+}
+
+fn add_vertex<T: Real>(
+    dataset: &mut Dataset<T>,
+    index: &mut HashMap<usize, usize>,
+    id: usize,
+    pose: SE3<T>,
+) -> Result<(), ParseError> {
+    if index.contains_key(&id) {
+        return Err(ParseError::DuplicateVertex(id));
+    }
+    index.insert(id, dataset.graph.add_node(pose));
+    dataset.ids.push(id);
+    Ok(())
+}
+
+fn add_edge<T: Real>(
+    dataset: &mut Dataset<T>,
+    i: usize,
+    j: usize,
+    measurement: SE3<T>,
+    information: Matrix6<T>,
+) {
+    // the nodes are checked when parsing their ids
+    dataset
+        .graph
+        .add_edge(i, j, measurement, information)
+        .unwrap();
+}
+
+fn planar<T: Real>(x: T, y: T, theta: T) -> SE3<T> {
+    SE3::new(
+        &SO3::from_euler_angles(T::zero(), T::zero(), theta),
+        [x, y, T::zero()],
+    )
+}
+
+/// `x`, `y` and yaw of the projection of the pose on the xy plane
+fn planar_parts<T: Real>(pose: &SE3<T>) -> [T; 3] {
+    let (r, p) = pose.rp();
+    [p[0], p[1], r[(1, 0)].atan2(r[(0, 0)])]
+}
+
+/// symmetric matrix from its upper triangle in row major order, with rows and columns reordered
+fn symmetric<T: Real, const N: usize>(order: &[usize; N], upper: &[T]) -> Matrix6<T> {
+    let mut m = Matrix6::zeros();
+    let mut k = 0;
+    for a in 0..N {
+        for b in a..N {
+            m[(order[a], order[b])] = upper[k];
+            m[(order[b], order[a])] = upper[k];
+            k += 1;
+        }
+    }
+    m
+}
+
+/// information with the rotation rows and columns scaled by `s`, from the quaternion error
+/// `[v; q.xyz]` of g2o with `q.xyz ≈ ω / 2` with `s = ½`, and back with `s = 2`
+fn scale_rotation<T: Real>(m: &Matrix6<T>, s: T) -> Matrix6<T> {
+    let scale = |k: usize| if k < 3 { s } else { T::one() };
+    Matrix6::from_fn(|a, b| m[(a, b)] * scale(a) * scale(b))
+}
+
+/// upper triangle in row major order, with rows and columns reordered
+fn upper<T: Real, const N: usize>(order: &[usize; N], m: &Matrix6<T>) -> Vec<T> {
+    let mut upper = Vec::with_capacity(N * (N + 1) / 2);
+    for a in 0..N {
+        for b in a..N {
+            upper.push(m[(order[a], order[b])]);
+        }
+    }
+    upper
+}
+
+/// planar information `[xx, xy, xθ, yy, yθ, θθ]` with unit information out of plane
+fn planar_information<T: Real>(upper: &[T; 6]) -> Matrix6<T> {
+    let mut m = symmetric(&PLANAR_ORDER, upper);
+    for k in [0, 1, 5] {
+        m[(k, k)] = T::one();
+    }
+    m
+}
+
+/// write a pose graph in the given format, with the node ids of the dataset
+///
+/// fails with [InvalidInput](std::io::ErrorKind::InvalidInput) if there are not as many ids as nodes
+pub fn write_g2o<T: Real, W: Write>(
+    mut writer: W,
+    dataset: &Dataset<T>,
+    format: Format,
+) -> std::io::Result<()> {
+    let Dataset { graph, ids } = dataset;
+    if ids.len() != graph.nodes().len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the number of ids and nodes differ",
+        ));
+    }
+    let (vertex_tag, edge_tag) = match format {
+        Format::G2oSe3 => ("VERTEX_SE3:QUAT", "EDGE_SE3:QUAT"),
+        Format::G2oSe2 => ("VERTEX_SE2", "EDGE_SE2"),
+        Format::Toro => ("VERTEX2", "EDGE2"),
+    };
+    for (id, node) in ids.iter().zip(graph.nodes()) {
+        write!(writer, "{vertex_tag} {id}")?;
+        write_pose(&mut writer, node, format)?;
+        writeln!(writer)?;
+    }
+    for edge in &graph.edges {
+        write!(writer, "{edge_tag} {} {}", ids[edge.from], ids[edge.to])?;
+        write_pose(&mut writer, &edge.measurement, format)?;
+        let upper = match format {
+            Format::G2oSe3 => upper(
+                &G2O_ORDER,
+                &scale_rotation(&edge.information, T::from(2.).unwrap()),
+            ),
+            Format::G2oSe2 => upper(&PLANAR_ORDER, &edge.information),
+            Format::Toro => {
+                let u = upper(&PLANAR_ORDER, &edge.information);
+                Vec::from([u[0], u[1], u[3], u[5], u[2], u[4]])
+            }
+        };
+        for x in upper {
+            write!(writer, " {}", to_f64(x))?;
+        }
+        writeln!(writer)?;
+    }
+    if format != Format::Toro {
+        for (i, id) in ids.iter().enumerate() {
+            if graph.is_fixed(i) {
+                writeln!(writer, "FIX {id}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_pose<T: Real, W: Write>(
+    writer: &mut W,
+    pose: &SE3<T>,
+    format: Format,
+) -> std::io::Result<()> {
+    let values: Vec<T> = match format {
+        Format::G2oSe3 => {
+            let (r, p) = pose.rp();
+            let q = SO3 { val: r }.quaternion();
+            p.iter().chain(&q).copied().collect()
+        }
+        Format::G2oSe2 | Format::Toro => planar_parts(pose).to_vec(),
+    };
+    for x in values {
+        write!(writer, " {}", to_f64(x))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    const SE3_FILE: &str = "\
+# a small 3D graph
+VERTEX_SE3:QUAT 10 0 0 0 0 0 0 1
+VERTEX_SE3:QUAT 11 1 0 0 0 0 0.7071067811865476 0.7071067811865476
+
+VERTEX_SE3:QUAT 12 1 1 0.5 0 0 1 0
+EDGE_SE3:QUAT 10 11 1 0 0 0 0 0.7071067811865476 0.7071067811865476 1 0 0 0 0 0 2 0 0 0 0 3 0 0 0 4 0 0.5 5 0 6
+EDGE_SE3:QUAT 11 12 1 0 0.5 0 0 0.7071067811865476 0.7071067811865476 1 0 0 0 0 0 1 0 0 0 0 1 0 0 0 1 0 0 1 0 1
+FIX 10
+";
+
+    #[test]
+    fn test_read_se3() {
+        let dataset = read_g2o::<f64, _>(SE3_FILE.as_bytes()).unwrap();
+        let graph = &dataset.graph;
+        assert_eq!(dataset.ids, [10, 11, 12]);
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(graph.edges.len(), 2);
+        assert!(graph.is_fixed(0) && !graph.is_fixed(1));
+        let yaw = |a: f64| SO3::from_euler_angles(0., 0., a);
+        assert_relative_eq!(
+            graph.nodes()[1],
+            SE3::new(&yaw(core::f64::consts::FRAC_PI_2), [1., 0., 0.]),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            graph.nodes()[2],
+            SE3::new(&yaw(core::f64::consts::PI), [1., 1., 0.5]),
+            epsilon = 1e-12
+        );
+        // the measurements are consistent with the nodes
+        for edge in &graph.edges {
+            assert_relative_eq!(edge.residual(graph.nodes()).val.norm(), 0., epsilon = 1e-12);
+        }
+        // x y z qx qy qz diagonal 1 2 3 4 5 6, with 0.5 between qx and qz
+        let info = graph.edges[0].information;
+        assert_eq!(info.diagonal().as_slice(), [1., 1.25, 1.5, 1., 2., 3.]);
+        assert_eq!((info[(0, 2)], info[(2, 0)]), (0.125, 0.125));
+    }
+
+    #[test]
+    fn test_quaternion_information() {
+        // diagonal 1 for x y z, 4 for qx qy qz, and 2 between y and qz
+        let file = "\
+VERTEX_SE3:QUAT 0 0 0 0 0 0 0 1
+VERTEX_SE3:QUAT 1 0 0 0 0 0 0 1
+EDGE_SE3:QUAT 0 1 0 0 0 0 0 0 1 1 0 0 0 0 0 1 0 0 0 2 1 0 0 0 4 0 0 4 0 4
+";
+        let dataset = read_g2o::<f64, _>(file.as_bytes()).unwrap();
+        let info = dataset.graph.edges[0].information;
+        // unit information on the rotation vector and the translation
+        assert_eq!(info.diagonal(), nalgebra::Vector6::repeat(1.));
+        assert_eq!((info[(2, 4)], info[(4, 2)]), (1., 1.));
+        assert_eq!(info.sum(), 8.);
+
+        // the cost of a small rotation is the same with both parameterizations
+        let omega = nalgebra::Vector6::new(0., 0., 1e-4, 0., 0., 0.);
+        let q = SO3::from_euler_angles(0., 0., 1e-4).quaternion();
+        let e_g2o = nalgebra::Vector6::new(0., 0., 0., q[0], q[1], q[2]);
+        let file_info = symmetric(
+            &[0, 1, 2, 3, 4, 5],
+            &[
+                1., 0., 0., 0., 0., 0., 1., 0., 0., 0., 2., 1., 0., 0., 0., 4., 0., 0., 4., 0., 4.,
+            ],
+        );
+        assert_relative_eq!(
+            omega.dot(&(info * omega)),
+            e_g2o.dot(&(file_info * e_g2o)),
+            max_relative = 1e-8
+        );
+
+        let mut out = Vec::new();
+        write_g2o(&mut out, &dataset, Format::G2oSe3).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert!(
+            line.contains(" 1 0 0 0 0 0 1 0 0 0 2 1 0 0 0 4 0 0 4 0 4\n"),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn test_read_planar() {
+        let file = "\
+VERTEX_SE2 0 0 0 0
+VERTEX2 1 1 2 0.5
+EDGE_SE2 0 1 1 2 0.5 1 2 3 4 5 6
+EDGE2 0 1 1 2 0.5 1 2 4 6 3 5
+";
+        let dataset = read_g2o::<f64, _>(file.as_bytes()).unwrap();
+        let graph = &dataset.graph;
+        assert_relative_eq!(
+            graph.nodes()[1],
+            SE3::new(&SO3::from_euler_angles(0., 0., 0.5), [1., 2., 0.]),
+            epsilon = 1e-12
+        );
+        // both orderings give the same information
+        assert_eq!(graph.edges[0].information, graph.edges[1].information);
+        let info = graph.edges[0].information;
+        assert_eq!(info.diagonal().as_slice(), [1., 1., 6., 1., 4., 1.]);
+        assert_eq!((info[(3, 4)], info[(3, 2)], info[(4, 2)]), (2., 3., 5.));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dataset = read_g2o::<f64, _>(SE3_FILE.as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_g2o(&mut out, &dataset, Format::G2oSe3).unwrap();
+        let read = read_g2o::<f64, _>(out.as_slice()).unwrap();
+        assert_eq!(read.ids, dataset.ids);
+        assert!(read.graph.is_fixed(0));
+        for (a, b) in read.graph.nodes().iter().zip(dataset.graph.nodes()) {
+            assert_relative_eq!(a, b, epsilon = 1e-15);
+        }
+        for (a, b) in read.graph.edges.iter().zip(&dataset.graph.edges) {
+            assert_eq!((a.from, a.to, a.information), (b.from, b.to, b.information));
+            assert_relative_eq!(a.measurement, b.measurement, epsilon = 1e-15);
+        }
+
+        for format in [Format::G2oSe2, Format::Toro] {
+            let file = "\
+VERTEX_SE2 3 0 0 0
+VERTEX_SE2 5 1 2 -2.5
+EDGE_SE2 3 5 1 2 -2.5 1 2 3 4 5 6
+";
+            let dataset = read_g2o::<f64, _>(file.as_bytes()).unwrap();
+            let mut out = Vec::new();
+            write_g2o(&mut out, &dataset, format).unwrap();
+            let read = read_g2o::<f64, _>(out.as_slice()).unwrap();
+            assert_eq!(read.ids, [3, 5]);
+            assert_relative_eq!(
+                read.graph.nodes()[1],
+                dataset.graph.nodes()[1],
+                epsilon = 1e-15
+            );
+            assert_eq!(
+                read.graph.edges[0].information,
+                dataset.graph.edges[0].information
+            );
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |file: &str| match read_g2o::<f64, _>(file.as_bytes()) {
+            Err(ReadError::Parse { line, error }) => (line, error),
+            _ => panic!("expected a parse error"),
+        };
+        assert_eq!(
+            error("VERTEX_SE2 0 0 0 0\n\nVERTEX_SE3 1 0 0 0\n"),
+            (3, ParseError::UnknownTag("VERTEX_SE3".into()))
+        );
+        assert_eq!(
+            error("VERTEX_SE2 0 0 0\n"),
+            (
+                1,
+                ParseError::FieldCount {
+                    expected: 4,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            error("VERTEX_SE2 0 0 x 0\n"),
+            (1, ParseError::InvalidNumber("x".into()))
+        );
+        assert_eq!(
+            error("VERTEX_SE2 0 0 nan 0\n"),
+            (1, ParseError::InvalidNumber("nan".into()))
+        );
+        assert_eq!(
+            error("VERTEX_SE2 -1 0 0 0\n"),
+            (1, ParseError::InvalidNumber("-1".into()))
+        );
+        assert_eq!(
+            error("VERTEX_SE3:QUAT 0 0 0 0 0 0 0 0\n"),
+            (1, ParseError::InvalidQuaternion)
+        );
+        assert_eq!(
+            error("VERTEX_SE2 0 0 0 0\nEDGE_SE2 0 1 1 2 0.5 1 0 0 1 0 1\n"),
+            (2, ParseError::UnknownVertex(1))
+        );
+        assert_eq!(
+            error("VERTEX_SE2 0 0 0 0\nVERTEX_SE2 0 0 0 0\n"),
+            (2, ParseError::DuplicateVertex(0))
+        );
+        assert_eq!(error("FIX 0\n"), (1, ParseError::UnknownVertex(0)));
+        assert_eq!(
+            ReadError::Parse {
+                line: 2,
+                error: ParseError::UnknownVertex(1)
+            }
+            .to_string(),
+            "line 2: unknown vertex 1"
+        );
+
+        let mut dataset = read_g2o::<f64, _>("VERTEX_SE2 0 0 0 0\n".as_bytes()).unwrap();
+        dataset.ids.clear();
+        let error = write_g2o(Vec::new(), &dataset, Format::G2oSe3).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
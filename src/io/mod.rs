@@ -0,0 +1,152 @@
+//! Reading and writing pose graph and trajectory files, requires the `std` feature
+//!
+//! parse errors carry the 1-based number of the offending line,
+//! empty lines and lines starting with `#` are skipped
+//!
+//! ```rust
+//! use liealg::io::{read_g2o, write_g2o, Format};
+//!
+//! let file = "\
+//! VERTEX_SE2 0 0 0 0
+//! VERTEX_SE2 1 1 0 1.5707963267948966
+//! EDGE_SE2 0 1 1 0 1.5707963267948966 1 0 0 1 0 1
+//! ";
+//! let dataset = read_g2o::<f64, _>(file.as_bytes()).unwrap();
+//! assert_eq!(dataset.graph.nodes().len(), 2);
+//! let mut out = Vec::new();
+//! write_g2o(&mut out, &dataset, Format::G2oSe3).unwrap();
+//! ```
+
+mod g2o;
+mod trajectory;
+
+use core::fmt::Display;
+use std::string::String;
+
+use crate::Real;
+
+pub use g2o::{read_g2o, write_g2o, Dataset, Format};
+pub use trajectory::{read_kitti, read_kitti_times, read_tum, write_kitti, write_tum};
+
+/// error of a file reader
+#[derive(Debug)]
+pub enum ReadError {
+    /// the underlying reader failed
+    Io(std::io::Error),
+    /// a line is malformed
+    Parse {
+        /// 1-based line number
+        line: usize,
+        /// what is wrong with the line
+        error: ParseError,
+    },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{e}"),
+            ReadError::Parse { line, error } => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+/// malformed line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// the line starts with an unsupported tag
+    UnknownTag(String),
+    /// the line has the wrong number of fields after the tag
+    FieldCount {
+        /// expected number of fields
+        expected: usize,
+        /// number of fields found
+        found: usize,
+    },
+    /// a field is not a finite number or not an id
+    InvalidNumber(String),
+    /// the quaternion is zero
+    InvalidQuaternion,
+    /// the rotation block is not close to a rotation matrix
+    InvalidRotation,
+    /// an edge or a `FIX` refers to a vertex which is not declared before
+    UnknownVertex(usize),
+    /// a vertex is declared twice
+    DuplicateVertex(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::UnknownTag(tag) => write!(f, "unknown tag `{tag}`"),
+            ParseError::FieldCount { expected, found } => {
+                write!(f, "expected {expected} fields, found {found}")
+            }
+            ParseError::InvalidNumber(s) => write!(f, "invalid number `{s}`"),
+            ParseError::InvalidQuaternion => write!(f, "zero quaternion"),
+            ParseError::InvalidRotation => write!(f, "not a rotation matrix"),
+            ParseError::UnknownVertex(id) => write!(f, "unknown vertex {id}"),
+            ParseError::DuplicateVertex(id) => write!(f, "duplicate vertex {id}"),
+        }
+    }
+}
+
+/// fields of a line after its tag
+fn fields<'a>(fields: &'a [&'a str], expected: usize) -> Result<&'a [&'a str], ParseError> {
+    if fields.len() == expected {
+        Ok(fields)
+    } else {
+        Err(ParseError::FieldCount {
+            expected,
+            found: fields.len(),
+        })
+    }
+}
+
+fn number<T: Real>(s: &str) -> Result<T, ParseError> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|x| x.is_finite())
+        .and_then(T::from)
+        .ok_or_else(|| ParseError::InvalidNumber(s.into()))
+}
+
+fn numbers<T: Real, const N: usize>(s: &[&str]) -> Result<[T; N], ParseError> {
+    let mut out = [T::zero(); N];
+    for (o, s) in out.iter_mut().zip(s) {
+        *o = number(s)?;
+    }
+    Ok(out)
+}
+
+fn id(s: &str) -> Result<usize, ParseError> {
+    s.parse().map_err(|_| ParseError::InvalidNumber(s.into()))
+}
+
+fn quaternion<T: Real>(q: [T; 4]) -> Result<[T; 4], ParseError> {
+    if q.iter().all(|x| *x == T::zero()) {
+        Err(ParseError::InvalidQuaternion)
+    } else {
+        Ok(q)
+    }
+}
+
+/// scalar as `f64` for writing
+fn to_f64<T: Real>(x: T) -> f64 {
+    x.to_f64().unwrap()
+}
@@ -0,0 +1,238 @@
+use std::{io::BufRead, io::Write, vec::Vec};
+
+use nalgebra::{Matrix3, Vector3};
+
+use super::{fields, numbers, quaternion, to_f64, ParseError, ReadError};
+use crate::{
+    trajectory::Stamped,
+    utils::{quat_to_rot, rot_to_quat},
+    Real, SE3, SO3,
+};
+
+/// maximum deviation of a KITTI rotation block from an orthonormal matrix
+const ROTATION_TOLERANCE: f64 = 1e-3;
+
+/// read the non empty and non comment lines, with their 1-based numbers
+fn read_lines<R: BufRead, O>(
+    reader: R,
+    mut parse: impl FnMut(&[&str]) -> Result<O, ParseError>,
+) -> Result<Vec<O>, ReadError> {
+    let mut out = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.first().is_none_or(|t| t.starts_with('#')) {
+            continue;
+        }
+        out.push(parse(&tokens).map_err(|error| ReadError::Parse { line: i + 1, error })?);
+    }
+    Ok(out)
+}
+
+/// read a TUM RGB-D trajectory, lines `timestamp tx ty tz qx qy qz qw`
+///
+/// the quaternions are normalized
+pub fn read_tum<T: Real, R: BufRead>(reader: R) -> Result<Vec<Stamped<T>>, ReadError> {
+    read_lines(reader, |tokens| {
+        let [time, x, y, z, qx, qy, qz, qw] = numbers(fields(tokens, 8)?)?;
+        let rot = SO3::from_quaternion(quaternion([qx, qy, qz, qw])?);
+        Ok(Stamped {
+            time,
+            pose: SE3::new(&rot, [x, y, z]),
+        })
+    })
+}
+
+/// write a TUM RGB-D trajectory, lines `timestamp tx ty tz qx qy qz qw`
+pub fn write_tum<T: Real, W: Write>(
+    mut writer: W,
+    trajectory: &[Stamped<T>],
+) -> std::io::Result<()> {
+    for Stamped { time, pose } in trajectory {
+        let (r, p) = pose.rp();
+        write!(writer, "{}", to_f64(*time))?;
+        for x in p.iter().chain(&rot_to_quat(&r)) {
+            write!(writer, " {}", to_f64(*x))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// read a KITTI odometry trajectory, lines of the 12 values of the 3x4 matrix `[R p]` in row major order
+///
+/// KITTI files have no timestamps, the time of each pose is its frame index,
+/// see [read_kitti_times] for the separate `times.txt` files.
+/// the rotation blocks are orthonormalized, and rejected if they are far from a rotation.
+pub fn read_kitti<T: Real, R: BufRead>(reader: R) -> Result<Vec<Stamped<T>>, ReadError> {
+    let mut frame = 0;
+    read_lines(reader, |tokens| {
+        let m: [T; 12] = numbers(fields(tokens, 12)?)?;
+        let r = Matrix3::new(m[0], m[1], m[2], m[4], m[5], m[6], m[8], m[9], m[10]);
+        let deviation = (r.transpose() * r - Matrix3::identity())
+            .iter()
+            .fold(T::zero(), |m, x| m.max(x.abs()));
+        let det = r.column(0).dot(&r.column(1).cross(&r.column(2)));
+        if deviation > T::from(ROTATION_TOLERANCE).unwrap() || det <= T::zero() {
+            return Err(ParseError::InvalidRotation);
+        }
+        // project on the rotations through the quaternion
+        let r = quat_to_rot(&rot_to_quat(&r));
+        let time = T::from(frame).unwrap();
+        frame += 1;
+        Ok(Stamped {
+            time,
+            pose: SE3::from_rp(&r, &Vector3::new(m[3], m[7], m[11])),
+        })
+    })
+}
+
+/// read a KITTI `times.txt` file, one timestamp in seconds per line
+///
+/// ```rust
+/// use liealg::io::{read_kitti, read_kitti_times};
+///
+/// let poses = "1 0 0 0 0 1 0 0 0 0 1 0\n1 0 0 1 0 1 0 0 0 0 1 0\n";
+/// let times = "0.0\n0.1\n";
+/// let mut trajectory = read_kitti::<f64, _>(poses.as_bytes()).unwrap();
+/// let times = read_kitti_times::<f64, _>(times.as_bytes()).unwrap();
+/// for (stamped, time) in trajectory.iter_mut().zip(times) {
+///     stamped.time = time;
+/// }
+/// assert_eq!(trajectory[1].time, 0.1);
+/// ```
+pub fn read_kitti_times<T: Real, R: BufRead>(reader: R) -> Result<Vec<T>, ReadError> {
+    read_lines(reader, |tokens| {
+        let [time] = numbers(fields(tokens, 1)?)?;
+        Ok(time)
+    })
+}
+
+/// write a KITTI odometry trajectory, the timestamps are not written
+pub fn write_kitti<T: Real, W: Write>(
+    mut writer: W,
+    trajectory: &[Stamped<T>],
+) -> std::io::Result<()> {
+    for Stamped { pose, .. } in trajectory {
+        let m = pose.matrix();
+        for row in 0..3 {
+            for col in 0..4 {
+                let sep = if row == 0 && col == 0 { "" } else { " " };
+                write!(writer, "{sep}{}", to_f64(m[(row, col)]))?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn trajectory() -> Vec<Stamped<f64>> {
+        (0..5)
+            .map(|i| {
+                let t = i as f64;
+                Stamped {
+                    time: 1305031102.175304 + 0.1 * t,
+                    pose: SE3::new(
+                        &SO3::from_euler_angles(0.1 * t, -0.2 * t, 0.7 * t),
+                        [t, 0.5 * t, -0.1 * t * t],
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tum() {
+        let file = "\
+# timestamp tx ty tz qx qy qz qw
+1305031102.1753 1.0 2.0 3.0 0 0 2 2
+1305031102.2113 1.5 2.0 3.0 0 0 0 1
+";
+        let trajectory = read_tum::<f64, _>(file.as_bytes()).unwrap();
+        assert_eq!(trajectory.len(), 2);
+        assert_eq!(trajectory[1].time, 1305031102.2113);
+        // the quaternion is normalized
+        let rot = SO3::from_euler_angles(0., 0., core::f64::consts::FRAC_PI_2);
+        assert_relative_eq!(
+            trajectory[0].pose,
+            SE3::new(&rot, [1., 2., 3.]),
+            epsilon = 1e-12
+        );
+
+        let trajectory = self::trajectory();
+        let mut out = Vec::new();
+        write_tum(&mut out, &trajectory).unwrap();
+        let read = read_tum::<f64, _>(out.as_slice()).unwrap();
+        for (a, b) in read.iter().zip(&trajectory) {
+            assert_eq!(a.time, b.time);
+            assert_relative_eq!(a.pose, b.pose, epsilon = 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_kitti() {
+        let file = "\
+1.000000e+00 0.000000e+00 0.000000e+00 0.000000e+00 0.000000e+00 1.000000e+00 0.000000e+00 0.000000e+00 0.000000e+00 0.000000e+00 1.000000e+00 0.000000e+00
+9.999978e-01 5.272628e-04 -2.066935e-03 -4.690294e-02 -5.296506e-04 9.999992e-01 -1.154865e-03 -2.839928e-02 2.066324e-03 1.155958e-03 9.999971e-01 8.586941e-01
+";
+        let trajectory = read_kitti::<f64, _>(file.as_bytes()).unwrap();
+        assert_eq!(trajectory.len(), 2);
+        assert_eq!((trajectory[0].time, trajectory[1].time), (0., 1.));
+        let (r, p) = trajectory[1].pose.rp();
+        // orthonormal after the projection, and close to the file values
+        assert_relative_eq!(r.transpose() * r, Matrix3::identity(), epsilon = 1e-14);
+        assert_relative_eq!(r[(0, 1)], 5.272628e-04, epsilon = 1e-6);
+        assert_eq!(p, Vector3::new(-4.690294e-02, -2.839928e-02, 8.586941e-01));
+
+        let trajectory = self::trajectory();
+        let mut out = Vec::new();
+        write_kitti(&mut out, &trajectory).unwrap();
+        let read = read_kitti::<f64, _>(out.as_slice()).unwrap();
+        for (a, b) in read.iter().zip(&trajectory) {
+            assert_relative_eq!(a.pose, b.pose, epsilon = 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |result: Result<Vec<Stamped<f64>>, ReadError>| match result {
+            Err(ReadError::Parse { line, error }) => (line, error),
+            _ => panic!("expected a parse error"),
+        };
+        assert_eq!(
+            error(read_tum(
+                "# header\n0 0 0 0 0 0 0 1\n0 0 0 0 0 0 1\n".as_bytes()
+            )),
+            (
+                3,
+                ParseError::FieldCount {
+                    expected: 8,
+                    found: 7
+                }
+            )
+        );
+        assert_eq!(
+            error(read_tum("0 0 0 0 0 0 0 0\n".as_bytes())),
+            (1, ParseError::InvalidQuaternion)
+        );
+        assert_eq!(
+            error(read_tum("0 0 0 inf 0 0 0 1\n".as_bytes())),
+            (1, ParseError::InvalidNumber("inf".into()))
+        );
+        assert_eq!(
+            error(read_kitti("2 0 0 0 0 1 0 0 0 0 1 0\n".as_bytes())),
+            (1, ParseError::InvalidRotation)
+        );
+        // a reflection
+        assert_eq!(
+            error(read_kitti("-1 0 0 0 0 1 0 0 0 0 1 0\n".as_bytes())),
+            (1, ParseError::InvalidRotation)
+        );
+    }
+}
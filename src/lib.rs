@@ -9,9 +9,14 @@
 //! liealg provides a set of mathematical entity traits for Lie group and Lie algebra.
 //! these are [Adjoint], [Algebra], [Group], [Vector].
 //! liealg provides a standard implementation of these traits, but users can also implement their own types.
+//! [Group] also provides `identity`, `plus` (⊕), `minus` (⊖) and `between`, and the dimensions
+//! [Group::DOF], [Algebra::DIM] and [Vector::DIM], so algorithms can be written generically over SO3 and SE3.
 //!
 //! liealg also provides a trait [Real] for using more types of real numbers, except f32, f64, it can support most real number types, such as rational and decimal, users can also define their own real number types.
 //!
+//! liealg bundles a dual number type [Dual] implementing [Real] for forward-mode automatic differentiation,
+//! `exp`, `log`, `adjoint` and `act` use Taylor expansions near zero angle, so derivatives stay exact at the identity.
+//!
 //! ## Implementations
 //!
 //! liealg provides two implementations of Lie group and Lie algebra, SO3 and SE3, which correspond to rotation and rigid body motion in 3D space.
@@ -21,13 +26,42 @@
 //! |SO3|so3|Vec3|
 //! |SE3|se3|Vec6|
 //!
+//! ## Jacobians
+//!
+//! [SO3] and [SE3] provide `rplus`, `lplus`, `rminus`, `lminus`, `compose`, `inverse`, `act_jac` and `log_jac`,
+//! [Vec3] and [Vec6] provide `exp_jac` and the left and right Jacobians `ljac`, `rjac`, `ljac_inv`, `rjac_inv`.
+//! The operations optionally write the Jacobians with respect to each input, defined on the right (local) tangent space.
+//! ```rust
+//! use liealg::{SO3, Vec3};
+//! use nalgebra::Matrix3;
+//! let rot = SO3::<f64>::identity();
+//! let mut j_tau = Matrix3::zeros();
+//! let res = rot.rplus(&Vec3::new(0., 0., 1.), None, Some(&mut j_tau));
+//! ```
+//!
 //! ## Usage
 //! add liealg to your dependencies
 //! ```toml
 //! [dependencies]
-//! liealg = "0.1"
+//! liealg = "0.5"
 //! ```
 //!
+//! ### Features
+//! - `alloc` (default): algorithms which allocate, such as [icp], [posegraph],
+//!   the [Trajectory](trajectory::Trajectory) container and the trajectory metrics of [eval].
+//! - `std`: implies `alloc`, reading and writing g2o and TORO pose graph files
+//!   and TUM RGB-D and KITTI trajectory files in [io].
+//! - `mint`: conversions between [SO3], [SE3], [Point], [Vec3] and the [mint](https://docs.rs/mint) types
+//!   `RowMatrix3`, `Quaternion`, `ColumnMatrix4`, `Point3` and `Vector3`,
+//!   for interoperability with other math libraries such as glam and cgmath.
+//! - `rand`: sampling with [rand](https://docs.rs/rand), Haar-uniform [SO3] from the `Standard` distribution,
+//!   [SE3] and [Point] uniform in a [Region](distributions::Region),
+//!   and gaussian [Vec3] and [Vec6] tangent vectors from a [Gaussian](distributions::Gaussian) with given covariance.
+//! - `serde`: `Serialize` and `Deserialize` for all group, algebra, adjoint, vector and point types.
+//!   rotations are written as unit quaternions `[x, y, z, w]`, [SE3] as
+//!   `{ "rotation": [x, y, z, w], "translation": [x, y, z] }`,
+//!   quaternions which are not normalized are rejected on deserialization.
+//!
 //! import prelude module
 //! ```rust
 //! use liealg::prelude::*;
@@ -89,24 +123,55 @@
 #![deny(missing_debug_implementations)]
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod batch;
+#[cfg(feature = "rand")]
+pub mod distributions;
+mod dual;
+#[cfg(feature = "alloc")]
+pub mod eval;
+pub mod frames;
+pub mod handeye;
+#[cfg(feature = "alloc")]
+pub mod icp;
 mod impl_approx;
 mod impl_clone;
+#[cfg(feature = "mint")]
+mod impl_mint;
+#[cfg(feature = "serde")]
+mod impl_serde;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod mean;
 mod point;
+#[cfg(feature = "alloc")]
+pub mod posegraph;
+pub mod registration;
 pub mod rigid;
+pub mod robust;
 pub mod rot;
+mod simd;
+pub mod tagged;
+pub mod trajectory;
 mod utils;
 
 use core::fmt::Debug;
 
+pub use dual::Dual;
 use num_traits::{real::Real as NumReal, FloatConst, NumAssignOps};
 pub use point::Point;
-pub use rigid::{se3, AdjSE3, Vec6, SE3};
-pub use rot::{so3, AdjSO3, Vec3, SO3};
+pub use rigid::{se3, AdjSE3, UncertainSE3, Vec6, SE3};
+pub use rot::{so3, AdjSO3, UncertainSO3, Vec3, SO3};
+pub use simd::SimdReal;
 pub use utils::*;
 
 /// prelude module
 pub mod prelude {
-    pub use crate::{Adjoint, Algebra, Group, Real, Vector};
+    pub use crate::{Adjoint, Algebra, Group, Perturbation, Propagation, Real, Vector};
 }
 /// # real number trait
 /// support ops: +, -, *, /, %, +=, -=, *=, /=, %=
@@ -114,15 +179,41 @@ pub mod prelude {
 /// consts: 0, 1, π, 1/π, ln2, ......
 ///
 /// compare ops: >, <, <=, >=
-pub trait Real: NumReal + Debug + NumAssignOps + FloatConst + 'static {}
+///
+/// every `Real` is a [SimdReal] with a single lane,
+/// the maps of the groups only require [SimdReal] and also accept SIMD lane types such as simba's `WideF32x8`
+pub trait Real: NumReal + Debug + NumAssignOps + FloatConst + SimdReal + 'static {}
+
+impl<T> Real for T where T: NumReal + Debug + NumAssignOps + FloatConst + SimdReal + 'static {}
+
+/// side on which a perturbation `exp(δ^)` is applied to a group element `X`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perturbation {
+    /// `exp(δ^) * X`, δ is expressed in the global frame
+    Left,
+    /// `X * exp(δ^)`, δ is expressed in the local frame
+    Right,
+}
 
-impl<T> Real for T where T: NumReal + Debug + NumAssignOps + FloatConst + 'static {}
+/// order of the covariance propagation when composing uncertain group elements
+///
+/// see Barfoot and Furgale, *Associating Uncertainty With Three-Dimensional Poses for Use in Estimation Problems*
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Propagation {
+    /// `Σ = Σ1 + Ad Σ2 Adᵀ`
+    SecondOrder,
+    /// second order with the fourth order terms of the BCH expansion
+    FourthOrder,
+}
 
 /// lie algebra vector representation
 pub trait Vector {
     /// algebra type
     type Algebra;
 
+    /// dimension of the vector
+    const DIM: usize;
+
     /// map vector to algebra
     fn hat(&self) -> Self::Algebra;
 }
@@ -133,7 +224,10 @@ pub trait Algebra {
     type Group;
 
     /// vector type
-    type Vector;
+    type Vector: Vector<Algebra = Self>;
+
+    /// dimension of the algebra
+    const DIM: usize;
 
     /// Exp map: map algebra to group
     fn exp(&self) -> Self::Group;
@@ -153,9 +247,23 @@ pub trait Adjoint {
 }
 
 /// Group trait
-pub trait Group {
+///
+/// algorithms can be written generically over any group, for example one step of gradient descent
+/// ```rust
+/// use liealg::prelude::*;
+/// fn step<G: Group>(g: &G, delta: &<G::Algebra as Algebra>::Vector) -> G {
+///     g.plus(delta)
+/// }
+/// ```
+pub trait Group: Sized {
+    /// degrees of freedom of the group
+    const DOF: usize;
+
+    /// identity element
+    fn identity() -> Self;
+
     /// algebra type
-    type Algebra;
+    type Algebra: Algebra<Group = Self>;
     /// log map, map group to algebra
     fn log(&self) -> Self::Algebra;
 
@@ -173,4 +281,21 @@ pub trait Group {
     type Point;
     /// matrix action on point
     fn act(&self, other: &Self::Point) -> Self::Point;
+
+    /// plus operator (⊕), `self * exp(tau^)`
+    fn plus(&self, tau: &<Self::Algebra as Algebra>::Vector) -> Self {
+        self.mat_mul(&tau.hat().exp())
+    }
+
+    /// minus operator (⊖), `log(other^-1 * self)∨`
+    ///
+    /// `other.plus(&self.minus(other)) == self`
+    fn minus(&self, other: &Self) -> <Self::Algebra as Algebra>::Vector {
+        other.between(self).log().vee()
+    }
+
+    /// relative element from self to other, `self^-1 * other`
+    fn between(&self, other: &Self) -> Self {
+        self.inv().mat_mul(other)
+    }
 }
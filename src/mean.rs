@@ -0,0 +1,318 @@
+//! Weighted means of rotations and poses
+//!
+//! ```rust
+//! use liealg::mean::MeanOptions;
+//! use liealg::SO3;
+//!
+//! let rots = [
+//!     SO3::from_euler_angles(0.1, 0., 0.),
+//!     SO3::from_euler_angles(-0.1, 0., 0.),
+//! ];
+//! let mean = SO3::karcher_mean(&rots, None, MeanOptions::default()).unwrap();
+//! assert!(mean.converged);
+//! let chordal = SO3::chordal_mean(&rots, None).unwrap();
+//! ```
+
+use core::fmt::Display;
+
+use nalgebra::{Matrix3, RealField, SVector};
+
+use crate::{
+    utils::{self, weight, WeightError},
+    Algebra, Group, Real, SE3, SO3,
+};
+
+/// options of the iterative Karcher mean
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeanOptions<T> {
+    /// maximum number of iterations
+    pub max_iterations: usize,
+    /// the iteration stops once the norm of the tangent space update is below the tolerance
+    pub tolerance: T,
+}
+
+impl<T: Real> Default for MeanOptions<T> {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            tolerance: T::epsilon().sqrt(),
+        }
+    }
+}
+
+/// result of the iterative Karcher mean
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mean<G, T> {
+    /// the mean
+    pub mean: G,
+    /// number of iterations performed
+    pub iterations: usize,
+    /// whether the last update is below the tolerance
+    pub converged: bool,
+    /// norm of the last tangent space update
+    pub step: T,
+}
+
+/// error of the mean algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeanError {
+    /// no element is given
+    Empty,
+    /// the number of weights differs from the number of elements
+    LengthMismatch,
+    /// a weight is negative or not finite, or all weights are zero
+    InvalidWeights,
+    /// the mean is not unique, e.g. the chordal mean of two rotations π apart
+    Degenerate,
+}
+
+impl Display for MeanError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MeanError::Empty => write!(f, "no element to average"),
+            MeanError::LengthMismatch => write!(f, "number of weights and elements differ"),
+            MeanError::InvalidWeights => write!(f, "invalid weights"),
+            MeanError::Degenerate => write!(f, "the mean is not unique"),
+        }
+    }
+}
+
+/// check the weights and return their sum
+fn weight_sum<T: Real>(len: usize, weights: Option<&[T]>) -> Result<T, MeanError> {
+    if len == 0 {
+        return Err(MeanError::Empty);
+    }
+    utils::weight_sum(len, weights).map_err(|e| match e {
+        WeightError::LengthMismatch => MeanError::LengthMismatch,
+        WeightError::Invalid => MeanError::InvalidWeights,
+    })
+}
+
+impl<T: Real> SO3<T> {
+    /// weighted Karcher (Fréchet) mean, minimizing `Σ wᵢ |log(mean⁻¹ Rᵢ)|²`
+    ///
+    /// starts from the element with the largest weight, and iterates
+    /// `mean = mean * exp(Σ wᵢ log(mean⁻¹ Rᵢ) / Σ wᵢ)`.
+    /// the mean is unique if all rotations lie in a ball of radius π/2
+    pub fn karcher_mean(
+        rots: &[Self],
+        weights: Option<&[T]>,
+        options: MeanOptions<T>,
+    ) -> Result<Mean<Self, T>, MeanError> {
+        let sum = weight_sum(rots.len(), weights)?;
+        karcher(
+            rots,
+            weights,
+            sum,
+            options,
+            |mean, rot| mean.between(rot).log().vee().val,
+            |mean, step| mean.mat_mul(&crate::so3 { val: *step }.exp()),
+        )
+    }
+}
+
+impl<T: Real + RealField> SO3<T> {
+    /// weighted chordal L2 mean, minimizing `Σ wᵢ |R - Rᵢ|²` over the Frobenius norm
+    ///
+    /// the closed form solution projects `Σ wᵢ Rᵢ` onto SO3 with an SVD
+    pub fn chordal_mean(rots: &[Self], weights: Option<&[T]>) -> Result<Self, MeanError> {
+        weight_sum(rots.len(), weights)?;
+        let mut m = Matrix3::zeros();
+        for (i, rot) in rots.iter().enumerate() {
+            m += rot.val * weight(weights, i);
+        }
+        let svd = m.svd(true, true);
+        let (u, v_t) = (svd.u.unwrap(), svd.v_t.unwrap());
+        // singular values are sorted in descending order,
+        // the projection is unique unless the two smallest vanish
+        let s = svd.singular_values;
+        let eps = <T as num_traits::real::Real>::epsilon();
+        if s[1] <= eps * s[0] {
+            return Err(MeanError::Degenerate);
+        }
+        let mut d = Matrix3::identity();
+        if (u * v_t).determinant() < T::zero() {
+            d[(2, 2)] = -T::one();
+        }
+        Ok(Self { val: u * d * v_t })
+    }
+}
+
+impl<T: Real> SE3<T> {
+    /// weighted Karcher (Fréchet) mean, minimizing `Σ wᵢ |log(mean⁻¹ Tᵢ)|²`
+    ///
+    /// starts from the element with the largest weight, and iterates
+    /// `mean = mean * exp(Σ wᵢ log(mean⁻¹ Tᵢ) / Σ wᵢ)`
+    pub fn karcher_mean(
+        poses: &[Self],
+        weights: Option<&[T]>,
+        options: MeanOptions<T>,
+    ) -> Result<Mean<Self, T>, MeanError> {
+        let sum = weight_sum(poses.len(), weights)?;
+        karcher(
+            poses,
+            weights,
+            sum,
+            options,
+            |mean, pose| mean.between(pose).log().vee().val,
+            |mean, step| mean.mat_mul(&crate::se3 { val: *step }.exp()),
+        )
+    }
+}
+
+/// Karcher mean iteration on the raw tangent vectors of a group
+fn karcher<G, T, const N: usize>(
+    elements: &[G],
+    weights: Option<&[T]>,
+    sum: T,
+    options: MeanOptions<T>,
+    log: impl Fn(&G, &G) -> SVector<T, N>,
+    plus: impl Fn(&G, &SVector<T, N>) -> G,
+) -> Result<Mean<G, T>, MeanError>
+where
+    G: Clone,
+    T: Real,
+{
+    let start = (0..elements.len())
+        .max_by(|&a, &b| {
+            weight(weights, a)
+                .partial_cmp(&weight(weights, b))
+                .unwrap_or(core::cmp::Ordering::Equal)
+        })
+        .unwrap_or(0);
+    let mut mean = elements[start].clone();
+    let mut step = T::zero();
+    for iterations in 1..=options.max_iterations {
+        let mut delta = SVector::zeros();
+        for (i, element) in elements.iter().enumerate() {
+            delta += log(&mean, element) * (weight(weights, i) / sum);
+        }
+        mean = plus(&mean, &delta);
+        step = delta.dot(&delta).sqrt();
+        if step <= options.tolerance {
+            return Ok(Mean {
+                mean,
+                iterations,
+                converged: true,
+                step,
+            });
+        }
+    }
+    Ok(Mean {
+        mean,
+        iterations: options.max_iterations,
+        converged: false,
+        step,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::{Vec3, Vec6};
+
+    use super::*;
+
+    fn rots() -> (SO3<f64>, [SO3<f64>; 4]) {
+        let center = SO3::from_euler_angles(0.3, -0.2, 1.);
+        let v = [Vec3::new(0.4, 0.1, -0.2), Vec3::new(-0.1, 0.3, 0.5)];
+        // symmetric about the center
+        let rots = [
+            center.plus(&v[0]),
+            center.plus(&(v[0].clone() * -1.)),
+            center.plus(&v[1]),
+            center.plus(&(v[1].clone() * -1.)),
+        ];
+        (center, rots)
+    }
+
+    #[test]
+    fn test_so3_karcher_mean() {
+        let (center, rots) = rots();
+        let mean = SO3::karcher_mean(&rots, None, MeanOptions::default()).unwrap();
+        assert!(mean.converged);
+        assert!(mean.step <= 1e-8);
+        assert_relative_eq!(mean.mean, center, epsilon = 1e-10);
+
+        // weighted mean lies on the geodesic
+        let a = SO3::from_euler_angles(0., 0., 0.2);
+        let b = SO3::from_euler_angles(0., 0., 1.);
+        let mean = SO3::karcher_mean(&[a, b], Some(&[1., 3.]), MeanOptions::default()).unwrap();
+        assert_relative_eq!(
+            mean.mean,
+            SO3::from_euler_angles(0., 0., 0.8),
+            epsilon = 1e-10
+        );
+
+        let options = MeanOptions {
+            max_iterations: 1,
+            tolerance: 1e-12,
+        };
+        let mean = SO3::karcher_mean(&rots, None, options).unwrap();
+        assert!(!mean.converged);
+        assert_eq!(mean.iterations, 1);
+    }
+
+    #[test]
+    fn test_so3_chordal_mean() {
+        let (center, rots) = rots();
+        let chordal = SO3::chordal_mean(&rots, None).unwrap();
+        let karcher = SO3::karcher_mean(&rots, None, MeanOptions::default()).unwrap();
+        // the means differ at the third order of the dispersion
+        assert_relative_eq!(chordal, karcher.mean, epsilon = 1e-2);
+        assert_relative_eq!(chordal, center, epsilon = 1e-2);
+        assert_relative_eq!(
+            SO3::chordal_mean(&rots[..1], None).unwrap(),
+            rots[0],
+            epsilon = 1e-12
+        );
+
+        let half_turn = SO3::from_euler_angles(0., 0., core::f64::consts::PI);
+        assert_eq!(
+            SO3::chordal_mean(&[SO3::identity(), half_turn], None),
+            Err(MeanError::Degenerate)
+        );
+    }
+
+    #[test]
+    fn test_se3_karcher_mean() {
+        let center = SE3::new(&SO3::from_euler_angles(0.3, -0.2, 1.), [1., 2., 3.]);
+        let v = [
+            Vec6::new([0.4, 0.1, -0.2], [1., 0., 0.5]),
+            Vec6::new([-0.1, 0.3, 0.5], [0., -2., 0.]),
+        ];
+        let poses = [
+            center.plus(&v[0]),
+            center.plus(&(v[0].clone() * -1.)),
+            center.plus(&v[1]),
+            center.plus(&(v[1].clone() * -1.)),
+        ];
+        let weights = [2., 2., 1., 1.];
+        let mean = SE3::karcher_mean(&poses, Some(&weights), MeanOptions::default()).unwrap();
+        assert!(mean.converged);
+        assert_relative_eq!(mean.mean, center, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = MeanOptions::default();
+        let (_, rots) = rots();
+        assert_eq!(
+            SO3::<f64>::karcher_mean(&[], None, options),
+            Err(MeanError::Empty)
+        );
+        assert_eq!(
+            SO3::karcher_mean(&rots, Some(&[1.]), options),
+            Err(MeanError::LengthMismatch)
+        );
+        assert_eq!(
+            SO3::karcher_mean(&rots, Some(&[1., -1., 1., 1.]), options),
+            Err(MeanError::InvalidWeights)
+        );
+        assert_eq!(
+            SO3::chordal_mean(&rots, Some(&[0.; 4])),
+            Err(MeanError::InvalidWeights)
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Pose graph optimization over SE3 nodes, requires the `alloc` feature
+//!
+//! an edge from node `i` to node `j` measures their relative pose `Z ≈ Tᵢ⁻¹ Tⱼ`, with residual
+//! ```text
+//! r = log(Z⁻¹ Tᵢ⁻¹ Tⱼ).vee()
+//! ```
+//! and cost `ρ(√(rᵀ Ω r))` for the 6x6 information matrix `Ω` in the `[ω; v]` ordering of [Vec6].
+//! nodes are updated on the right, `Tᵢ = Tᵢ * exp(δᵢ^)`, so the Jacobians are
+//! ```text
+//! ∂r/∂δⱼ = Jr⁻¹(r)
+//! ∂r/∂δᵢ = -Jr⁻¹(r) Ad(Tⱼ⁻¹ Tᵢ)
+//! ```
+//!
+//! ```rust
+//! use liealg::posegraph::{PoseGraph, PoseGraphOptions};
+//! use liealg::prelude::*;
+//! use liealg::{Vec6, SE3};
+//! use nalgebra::Matrix6;
+//!
+//! let step = Vec6::new([0., 0., 0.5], [1., 0., 0.]).hat().exp();
+//! let mut graph = PoseGraph::new();
+//! let a = graph.add_node(SE3::identity());
+//! let b = graph.add_node(step.clone());
+//! graph.add_edge(a, b, step, Matrix6::identity()).unwrap();
+//! let summary = graph.optimize(&PoseGraphOptions::default()).unwrap();
+//! assert!(summary.converged);
+//! ```
+
+mod sparse;
+
+use core::fmt::Display;
+
+use alloc::{vec, vec::Vec};
+use nalgebra::{Matrix6, Vector6};
+
+use crate::{robust::Kernel, Algebra, Group, Real, Vec6, SE3};
+
+use sparse::BlockMatrix;
+
+/// relative pose measurement between two nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge<T> {
+    /// index of the first node `i`
+    pub from: usize,
+    /// index of the second node `j`
+    pub to: usize,
+    /// measured relative pose `Z ≈ Tᵢ⁻¹ Tⱼ`
+    pub measurement: SE3<T>,
+    /// information matrix, the inverse of the covariance of the residual
+    pub information: Matrix6<T>,
+}
+
+impl<T: Real> Edge<T> {
+    /// residual `log(Z⁻¹ Tᵢ⁻¹ Tⱼ).vee()`
+    pub fn residual(&self, nodes: &[SE3<T>]) -> Vec6<T> {
+        let (ti, tj) = (&nodes[self.from], &nodes[self.to]);
+        self.measurement
+            .inv()
+            .mat_mul(&ti.inv())
+            .mat_mul(tj)
+            .log()
+            .vee()
+    }
+
+    /// residual and its Jacobians with respect to the right perturbations of `Tᵢ` and `Tⱼ`
+    fn linearize(&self, nodes: &[SE3<T>]) -> (Vector6<T>, Matrix6<T>, Matrix6<T>) {
+        let (ti, tj) = (&nodes[self.from], &nodes[self.to]);
+        let r = self.residual(nodes);
+        let jj = r.rjac_inv();
+        let ji = -jj * tj.inv().mat_mul(ti).adjoint().val;
+        (r.val, ji, jj)
+    }
+}
+
+/// minimization method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// undamped Gauss-Newton steps
+    GaussNewton,
+    /// Gauss-Newton steps damped by `λ diag(H)`, only accepted if they lower the cost
+    LevenbergMarquardt,
+}
+
+/// options of the pose graph optimization
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoseGraphOptions<T> {
+    /// minimization method
+    pub method: Method,
+    /// maximum number of iterations
+    pub max_iterations: usize,
+    /// the iteration stops once the norm of the update is below the tolerance
+    pub tolerance: T,
+    /// robust kernel of the edges
+    pub kernel: Kernel<T>,
+}
+
+impl<T: Real> Default for PoseGraphOptions<T> {
+    fn default() -> Self {
+        Self {
+            method: Method::LevenbergMarquardt,
+            max_iterations: 100,
+            tolerance: T::epsilon().sqrt(),
+            kernel: Kernel::L2,
+        }
+    }
+}
+
+/// summary of the pose graph optimization
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary<T> {
+    /// number of iterations performed
+    pub iterations: usize,
+    /// whether the last update is below the tolerance
+    pub converged: bool,
+    /// cost before the optimization
+    pub initial_cost: T,
+    /// cost after the optimization
+    pub final_cost: T,
+}
+
+/// error of the pose graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoseGraphError {
+    /// an edge refers to a node which does not exist
+    InvalidNode(usize),
+    /// the normal equations are singular, e.g. a free node has no edge
+    Singular,
+}
+
+impl Display for PoseGraphError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PoseGraphError::InvalidNode(i) => write!(f, "invalid node {i}"),
+            PoseGraphError::Singular => write!(f, "singular normal equations"),
+        }
+    }
+}
+
+/// graph of SE3 nodes connected by relative pose measurements
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoseGraph<T> {
+    nodes: Vec<SE3<T>>,
+    /// relative pose measurements
+    pub edges: Vec<Edge<T>>,
+    fixed: Vec<bool>,
+}
+
+impl<T: Real> Default for PoseGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Real> PoseGraph<T> {
+    /// empty graph
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            fixed: Vec::new(),
+        }
+    }
+
+    /// poses of the nodes
+    pub fn nodes(&self) -> &[SE3<T>] {
+        &self.nodes
+    }
+
+    /// mutable poses of the nodes, nodes are only added by [PoseGraph::add_node]
+    pub fn nodes_mut(&mut self) -> &mut [SE3<T>] {
+        &mut self.nodes
+    }
+
+    /// pose of node `i`
+    pub fn node(&self, i: usize) -> Option<&SE3<T>> {
+        self.nodes.get(i)
+    }
+
+    /// one fixed flag per node, nodes without a flag are free
+    fn resize_fixed(&mut self) {
+        self.fixed.resize(self.nodes.len(), false);
+    }
+
+    /// add a node and return its index
+    pub fn add_node(&mut self, pose: SE3<T>) -> usize {
+        self.nodes.push(pose);
+        self.fixed.push(false);
+        self.nodes.len() - 1
+    }
+
+    /// add an edge measuring `Z ≈ Tᵢ⁻¹ Tⱼ` from node `i` to node `j`
+    pub fn add_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        measurement: SE3<T>,
+        information: Matrix6<T>,
+    ) -> Result<(), PoseGraphError> {
+        for node in [from, to] {
+            if node >= self.nodes.len() {
+                return Err(PoseGraphError::InvalidNode(node));
+            }
+        }
+        self.edges.push(Edge {
+            from,
+            to,
+            measurement,
+            information,
+        });
+        Ok(())
+    }
+
+    /// fix or free a node, fixed nodes are not optimized
+    ///
+    /// the cost is invariant to a global transform of all the nodes,
+    /// the first node is fixed during the optimization if no node is
+    pub fn set_fixed(&mut self, node: usize, fixed: bool) -> Result<(), PoseGraphError> {
+        self.resize_fixed();
+        *self
+            .fixed
+            .get_mut(node)
+            .ok_or(PoseGraphError::InvalidNode(node))? = fixed;
+        Ok(())
+    }
+
+    /// whether the node is fixed
+    pub fn is_fixed(&self, node: usize) -> bool {
+        self.fixed.get(node).copied().unwrap_or(false)
+    }
+
+    /// total cost `Σ ρ(√(rᵀ Ω r))` of the edges
+    pub fn cost(&self, kernel: &Kernel<T>) -> T {
+        cost(&self.nodes, &self.edges, kernel)
+    }
+
+    /// minimize the cost over the free nodes
+    pub fn optimize(
+        &mut self,
+        options: &PoseGraphOptions<T>,
+    ) -> Result<Summary<T>, PoseGraphError> {
+        for edge in &self.edges {
+            for node in [edge.from, edge.to] {
+                if node >= self.nodes.len() {
+                    return Err(PoseGraphError::InvalidNode(node));
+                }
+            }
+        }
+        self.resize_fixed();
+        // index of the variables of each free node
+        let mut index = vec![None; self.nodes.len()];
+        let gauge = !self.fixed.iter().any(|&f| f);
+        let mut n = 0;
+        for (i, fixed) in self.fixed.iter().enumerate() {
+            if !(*fixed || gauge && i == 0) {
+                index[i] = Some(n);
+                n += 1;
+            }
+        }
+
+        let initial_cost = self.cost(&options.kernel);
+        let mut summary = Summary {
+            iterations: 0,
+            converged: false,
+            initial_cost,
+            final_cost: initial_cost,
+        };
+        let lm = options.method == Method::LevenbergMarquardt;
+        let ten = T::from(10).unwrap();
+        let mut lambda = T::from(1e-4).unwrap();
+        let mut normal = None;
+        while summary.iterations < options.max_iterations {
+            summary.iterations += 1;
+            // rejected steps reuse the linearization at the same nodes
+            let (h, g) =
+                normal.get_or_insert_with(|| self.normal_equations(&index, n, &options.kernel));
+            let mut h = h.clone();
+            if lm {
+                h.damp(lambda);
+            }
+            let delta = h.solve(g).ok_or(PoseGraphError::Singular)?;
+            let step = delta.iter().fold(T::zero(), |s, d| s + d.dot(d)).sqrt();
+
+            let mut nodes = self.nodes.clone();
+            for (node, i) in nodes.iter_mut().zip(&index) {
+                if let Some(i) = i {
+                    *node = node.plus(&Vec6 { val: -delta[*i] });
+                }
+            }
+            let new_cost = cost(&nodes, &self.edges, &options.kernel);
+            if lm && new_cost > summary.final_cost {
+                lambda *= ten;
+            } else {
+                lambda = (lambda / ten).max(T::epsilon());
+                self.nodes = nodes;
+                summary.final_cost = new_cost;
+                normal = None;
+            }
+            if step <= options.tolerance {
+                summary.converged = true;
+                break;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// `H = Σ w JᵀΩJ` and `g = Σ w JᵀΩr` over the free nodes
+    fn normal_equations(
+        &self,
+        index: &[Option<usize>],
+        n: usize,
+        kernel: &Kernel<T>,
+    ) -> (BlockMatrix<T>, Vec<Vector6<T>>) {
+        let mut h = BlockMatrix::new(n);
+        let mut g = vec![Vector6::zeros(); n];
+        for edge in &self.edges {
+            let (r, ji, jj) = edge.linearize(&self.nodes);
+            let w = kernel.weight(r.dot(&(edge.information * r)).sqrt());
+            let info = edge.information * w;
+            let blocks = [(index[edge.from], ji), (index[edge.to], jj)];
+            for (a, ja) in &blocks {
+                let Some(a) = a else { continue };
+                g[*a] += ja.transpose() * info * r;
+                for (b, jb) in &blocks {
+                    let Some(b) = b else { continue };
+                    // each unordered pair once, the diagonal once per node
+                    if b <= a {
+                        h.add(*a, *b, &(ja.transpose() * info * jb));
+                    }
+                }
+            }
+        }
+        (h, g)
+    }
+}
+
+fn cost<T: Real>(nodes: &[SE3<T>], edges: &[Edge<T>], kernel: &Kernel<T>) -> T {
+    edges.iter().fold(T::zero(), |c, edge| {
+        let r = edge.residual(nodes).val;
+        c + kernel.cost(r.dot(&(edge.information * r)).sqrt())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use core::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::SO3;
+
+    fn pose(rpy: [f64; 3], p: [f64; 3]) -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(rpy[0], rpy[1], rpy[2]), p)
+    }
+
+    /// poses around a loop of 8 steps, slightly out of plane
+    fn truth() -> Vec<SE3<f64>> {
+        let step = pose([0.02, -0.03, PI / 4.], [1., 0., 0.05]);
+        let mut nodes = vec![pose([0.1, 0.2, 0.3], [1., 2., 3.])];
+        for _ in 1..8 {
+            nodes.push(nodes.last().unwrap().mat_mul(&step));
+        }
+        nodes
+    }
+
+    /// odometry edges around the loop, the loop closure and a diagonal
+    fn graph(nodes: &[SE3<f64>]) -> PoseGraph<f64> {
+        let mut graph = PoseGraph::new();
+        let mut drift = SE3::identity();
+        for (i, node) in nodes.iter().enumerate() {
+            // accumulated odometry drift in the initial guess
+            drift = drift.mat_mul(&pose([0.01, -0.02, 0.03 * i as f64], [0.05, -0.1, 0.02]));
+            let guess = if i == 0 {
+                node.clone()
+            } else {
+                node.mat_mul(&drift)
+            };
+            graph.add_node(guess);
+        }
+        let n = nodes.len();
+        for (i, j) in (0..n).map(|i| (i, (i + 1) % n)).chain([(1, 5)]) {
+            let z = nodes[i].inv().mat_mul(&nodes[j]);
+            graph.add_edge(i, j, z, Matrix6::identity()).unwrap();
+        }
+        graph
+    }
+
+    fn assert_nodes_eq(a: &[SE3<f64>], b: &[SE3<f64>], epsilon: f64) {
+        for (a, b) in a.iter().zip(b) {
+            assert_relative_eq!(a, b, epsilon = epsilon);
+        }
+    }
+
+    #[test]
+    fn test_jacobians() {
+        let nodes = [
+            pose([0.3, -0.2, 1.], [1., 2., 0.5]),
+            pose([-0.5, 0.4, 2.], [0., -1., 1.]),
+        ];
+        let edge = Edge {
+            from: 0,
+            to: 1,
+            measurement: pose([0.2, 0.1, 0.9], [-1., 0.5, 0.3]),
+            information: Matrix6::identity(),
+        };
+        let (r, ji, jj) = edge.linearize(&nodes);
+        let h = 1e-6;
+        for (node, jac) in [(0, ji), (1, jj)] {
+            for k in 0..6 {
+                let mut delta = Vector6::zeros();
+                delta[k] = h;
+                let mut perturbed = nodes.clone();
+                perturbed[node] = nodes[node].plus(&Vec6 { val: delta });
+                let d = (edge.residual(&perturbed).val - r) / h;
+                assert_relative_eq!(d, jac.column(k).into_owned(), epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_synthetic_loop() {
+        let truth = truth();
+        for method in [Method::GaussNewton, Method::LevenbergMarquardt] {
+            let mut graph = graph(&truth);
+            let options = PoseGraphOptions {
+                method,
+                ..Default::default()
+            };
+            let summary = graph.optimize(&options).unwrap();
+            assert!(summary.converged);
+            assert!(summary.initial_cost > 1.);
+            assert!(summary.final_cost < 1e-20);
+            // the first node fixes the gauge
+            assert_nodes_eq(graph.nodes(), &truth, 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_fixed_node() {
+        let truth = truth();
+        let mut graph = graph(&truth);
+        graph.nodes_mut()[3] = truth[3].clone();
+        graph.set_fixed(3, true).unwrap();
+        assert!(graph.is_fixed(3) && !graph.is_fixed(0));
+        let summary = graph.optimize(&PoseGraphOptions::default()).unwrap();
+        assert!(summary.converged);
+        assert_nodes_eq(graph.nodes(), &truth, 1e-9);
+    }
+
+    #[test]
+    fn test_pushed_node() {
+        let truth = truth();
+        let mut graph = graph(&truth[..7]);
+        // a node pushed without add_node is free
+        graph
+            .nodes
+            .push(truth[7].mat_mul(&pose([0.1, 0., -0.2], [0.3, 0.1, 0.])));
+        for (i, j) in [(6, 7), (7, 0)] {
+            let z = truth[i].inv().mat_mul(&truth[j]);
+            graph.add_edge(i, j, z, Matrix6::identity()).unwrap();
+        }
+        assert!(!graph.is_fixed(7));
+        let summary = graph.optimize(&PoseGraphOptions::default()).unwrap();
+        assert!(summary.converged);
+        assert_nodes_eq(graph.nodes(), &truth, 1e-9);
+        assert_relative_eq!(graph.node(7).unwrap(), &truth[7], epsilon = 1e-9);
+        graph.set_fixed(7, true).unwrap();
+        assert!(graph.is_fixed(7));
+
+        // shrinking the nodes drops their flags
+        graph.nodes.truncate(2);
+        graph.edges.retain(|e| e.from < 2 && e.to < 2);
+        graph.optimize(&PoseGraphOptions::default()).unwrap();
+        graph.nodes.push(SE3::identity());
+        assert!(!graph.is_fixed(2));
+    }
+
+    #[test]
+    fn test_robust_outlier() {
+        let truth = truth();
+        let mut graph = graph(&truth);
+        // wrong loop closure
+        graph
+            .add_edge(
+                2,
+                6,
+                pose([0.5, 0.3, -1.], [2., 1., -1.]),
+                Matrix6::identity(),
+            )
+            .unwrap();
+        let error = |kernel| {
+            let mut graph = graph.clone();
+            let options = PoseGraphOptions {
+                kernel,
+                ..Default::default()
+            };
+            graph.optimize(&options).unwrap();
+            graph
+                .nodes()
+                .iter()
+                .zip(&truth)
+                .map(|(a, b)| a.minus(b).val.norm())
+                .fold(0., f64::max)
+        };
+        let l2 = error(Kernel::L2);
+        let cauchy = error(Kernel::Cauchy(0.1));
+        assert!(l2 > 0.1);
+        assert!(cauchy < 0.01 * l2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut graph = PoseGraph::new();
+        let a = graph.add_node(SE3::<f64>::identity());
+        let b = graph.add_node(SE3::identity());
+        assert_eq!(
+            graph.add_edge(a, 2, SE3::identity(), Matrix6::identity()),
+            Err(PoseGraphError::InvalidNode(2))
+        );
+        assert_eq!(
+            graph.set_fixed(5, true),
+            Err(PoseGraphError::InvalidNode(5))
+        );
+        // b is free but unconstrained
+        assert_eq!(
+            graph.optimize(&PoseGraphOptions::default()),
+            Err(PoseGraphError::Singular)
+        );
+        graph.set_fixed(a, true).unwrap();
+        graph.set_fixed(b, true).unwrap();
+        // all the nodes are fixed
+        let summary = graph.optimize(&PoseGraphOptions::default()).unwrap();
+        assert!(summary.converged);
+        assert_eq!(summary.final_cost, 0.);
+    }
+}
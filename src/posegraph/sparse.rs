@@ -0,0 +1,181 @@
+use alloc::{collections::BTreeMap, collections::BTreeSet, vec, vec::Vec};
+use nalgebra::{Matrix6, SMatrix, Vector6};
+
+use crate::{utils::cholesky, Real};
+
+/// symmetric block sparse matrix of 6x6 blocks, storing the lower triangle
+#[derive(Debug, Clone)]
+pub(crate) struct BlockMatrix<T> {
+    /// blocks `(i, j)` with `j <= i` of each row `i`
+    rows: Vec<BTreeMap<usize, Matrix6<T>>>,
+}
+
+impl<T: Real> BlockMatrix<T> {
+    pub(crate) fn new(n: usize) -> Self {
+        Self {
+            rows: vec![BTreeMap::new(); n],
+        }
+    }
+
+    /// add to the block `(i, j)`, and implicitly to its transpose `(j, i)`
+    pub(crate) fn add(&mut self, i: usize, j: usize, block: &Matrix6<T>) {
+        let (i, j, block) = if j <= i {
+            (i, j, *block)
+        } else {
+            (j, i, block.transpose())
+        };
+        *self.rows[i].entry(j).or_insert_with(Matrix6::zeros) += block;
+    }
+
+    /// add `λ diag(A)` to the diagonal, the Levenberg-Marquardt damping of Marquardt
+    pub(crate) fn damp(&mut self, lambda: T) {
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if let Some(d) = row.get_mut(&i) {
+                for k in 0..6 {
+                    let v = d[(k, k)];
+                    d[(k, k)] = v + lambda * v;
+                }
+            }
+        }
+    }
+
+    /// solve `A x = b` by block Cholesky factorization `A = L Lᵀ` in the natural order,
+    /// `None` if `A` is not positive definite
+    pub(crate) fn solve(mut self, b: &[Vector6<T>]) -> Option<Vec<Vector6<T>>> {
+        let n = self.rows.len();
+        // rows below the diagonal having a block in each column, grows with the fill-in
+        let mut cols: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); n];
+        for (i, row) in self.rows.iter().enumerate() {
+            for &j in row.keys().filter(|&&j| j < i) {
+                cols[j].insert(i);
+            }
+        }
+        for k in 0..n {
+            let l_kk = cholesky(self.rows[k].get(&k)?)?;
+            self.rows[k].insert(k, l_kk);
+            let below: Vec<usize> = cols[k].iter().copied().collect();
+            // L_ik = A_ik L_kk⁻ᵀ
+            for &i in &below {
+                let a_ik = self.rows[i][&k];
+                let l_ik = lower_solve(&l_kk, &a_ik.transpose()).transpose();
+                self.rows[i].insert(k, l_ik);
+            }
+            // A_ij -= L_ik L_jkᵀ
+            for (a, &i) in below.iter().enumerate() {
+                let l_ik = self.rows[i][&k];
+                for &j in &below[..=a] {
+                    let update = l_ik * self.rows[j][&k].transpose();
+                    let block = self.rows[i].entry(j).or_insert_with(|| {
+                        cols[j].insert(i);
+                        Matrix6::zeros()
+                    });
+                    *block -= update;
+                }
+            }
+        }
+        // L y = b
+        let mut x = b.to_vec();
+        for i in 0..n {
+            let mut s = x[i];
+            for (&j, l_ij) in self.rows[i].range(..i) {
+                s -= l_ij * x[j];
+            }
+            x[i] = lower_solve(&self.rows[i][&i], &s);
+        }
+        // Lᵀ x = y
+        for i in (0..n).rev() {
+            let mut s = x[i];
+            for &k in &cols[i] {
+                s -= self.rows[k][&i].transpose() * x[k];
+            }
+            x[i] = upper_solve(&self.rows[i][&i].transpose(), &s);
+        }
+        Some(x)
+    }
+}
+
+/// solve `L X = B` for a lower triangular `L`
+fn lower_solve<T: Real, const C: usize>(l: &Matrix6<T>, b: &SMatrix<T, 6, C>) -> SMatrix<T, 6, C> {
+    let mut x = *b;
+    for c in 0..C {
+        for i in 0..6 {
+            let mut s = x[(i, c)];
+            for k in 0..i {
+                s -= l[(i, k)] * x[(k, c)];
+            }
+            x[(i, c)] = s / l[(i, i)];
+        }
+    }
+    x
+}
+
+/// solve `U x = b` for an upper triangular `U`
+fn upper_solve<T: Real>(u: &Matrix6<T>, b: &Vector6<T>) -> Vector6<T> {
+    let mut x = *b;
+    for i in (0..6).rev() {
+        let mut s = x[i];
+        for k in i + 1..6 {
+            s -= u[(i, k)] * x[k];
+        }
+        x[i] = s / u[(i, i)];
+    }
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::DMatrix;
+
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        // deterministic pseudo random blocks of a chain with a loop, which fills in
+        let mut state = 7u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let n = 5;
+        let mut a = BlockMatrix::new(n);
+        let mut dense = DMatrix::<f64>::zeros(6 * n, 6 * n);
+        let mut add = |a: &mut BlockMatrix<f64>, i: usize, j: usize, block: Matrix6<f64>| {
+            a.add(i, j, &block);
+            let mut view = dense.view_mut((6 * i, 6 * j), (6, 6));
+            view += block;
+            if i != j {
+                let mut view = dense.view_mut((6 * j, 6 * i), (6, 6));
+                view += block.transpose();
+            }
+        };
+        for (i, j) in (0..n).map(|i| (i, (i + 1) % n)) {
+            let jac = Matrix6::from_fn(|_, _| next());
+            add(&mut a, i, i, jac.transpose() * jac + Matrix6::identity());
+            add(&mut a, j, j, Matrix6::identity());
+            add(&mut a, i, j, jac.transpose());
+        }
+        let b: Vec<_> = (0..n).map(|_| Vector6::from_fn(|_, _| next())).collect();
+        let x = a.solve(&b).unwrap();
+        for (i, b) in b.iter().enumerate() {
+            let ax = (0..n).fold(Vector6::zeros(), |s, j| {
+                s + dense.view((6 * i, 6 * j), (6, 6)) * x[j]
+            });
+            assert_relative_eq!(ax, b, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_not_positive_definite() {
+        let mut a = BlockMatrix::<f64>::new(2);
+        a.add(0, 0, &Matrix6::identity());
+        a.add(1, 1, &Matrix6::identity());
+        a.add(1, 0, &(Matrix6::identity() * 2.));
+        assert!(a.solve(&[Vector6::zeros(); 2]).is_none());
+        // a missing diagonal block
+        let a = BlockMatrix::<f64>::new(1);
+        assert!(a.solve(&[Vector6::zeros()]).is_none());
+    }
+}
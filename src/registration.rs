@@ -0,0 +1,373 @@
+//! Registration of corresponding point sets
+//!
+//! every solver finds the transform mapping the `src` points onto the `dst` points,
+//! minimizing `Σ wᵢ |dstᵢ - f(srcᵢ)|²`
+//!
+//! ```rust
+//! use liealg::prelude::*;
+//! use liealg::registration::umeyama;
+//! use liealg::{Point, SE3, SO3};
+//!
+//! let pose = SE3::new(&SO3::from_euler_angles(0.1, 0.2, 0.3), [1., 2., 3.]);
+//! let src = [
+//!     Point::new(0., 0., 0.),
+//!     Point::new(1., 0., 0.),
+//!     Point::new(0., 1., 0.),
+//!     Point::new(0., 0., 1.),
+//! ];
+//! let dst = src.clone().map(|p| pose.act(&p));
+//! let pose_ = umeyama(&src, &dst, None).unwrap();
+//! ```
+
+use core::fmt::Display;
+
+use nalgebra::{Matrix3, Matrix4, RealField, SymmetricEigen, Vector3};
+
+use crate::{
+    utils::{quat_to_rot, weight, weight_sum, WeightError},
+    Point, Real, SE3, SO3,
+};
+
+/// error of the registration solvers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationError {
+    /// the point sets, or the weights, have different lengths
+    LengthMismatch,
+    /// a weight is negative or not finite, or all weights are zero
+    InvalidWeights,
+    /// fewer points than the solver requires
+    TooFewPoints,
+    /// the points are coincident or collinear, the transform is not unique
+    Degenerate,
+}
+
+impl Display for RegistrationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RegistrationError::LengthMismatch => {
+                write!(f, "point sets or weights differ in length")
+            }
+            RegistrationError::InvalidWeights => write!(f, "invalid weights"),
+            RegistrationError::TooFewPoints => write!(f, "too few points"),
+            RegistrationError::Degenerate => write!(f, "degenerate point configuration"),
+        }
+    }
+}
+
+/// similarity transform `p ↦ s R p + t`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Similarity<T> {
+    /// rotation and translation
+    pub pose: SE3<T>,
+    /// scale
+    pub scale: T,
+}
+
+impl<T: Real> Similarity<T> {
+    /// transform a point
+    pub fn act(&self, p: &Point<T>) -> Point<T> {
+        let (r, t) = self.pose.rp();
+        Point {
+            val: r * p.val * self.scale + t,
+        }
+    }
+}
+
+/// weighted sums needed by every solver
+struct Moments<T> {
+    src_mean: Vector3<T>,
+    dst_mean: Vector3<T>,
+    /// `Σ wᵢ (srcᵢ - src_mean)(dstᵢ - dst_mean)ᵀ / Σ wᵢ`
+    cross: Matrix3<T>,
+    /// `Σ wᵢ |srcᵢ - src_mean|² / Σ wᵢ`
+    src_var: T,
+}
+
+fn moments<T: Real>(
+    src: &[Point<T>],
+    dst: &[Point<T>],
+    weights: Option<&[T]>,
+    centered: bool,
+    min_points: usize,
+) -> Result<Moments<T>, RegistrationError> {
+    if src.len() != dst.len() {
+        return Err(RegistrationError::LengthMismatch);
+    }
+    let sum = weight_sum(src.len(), weights).map_err(|e| match e {
+        WeightError::LengthMismatch => RegistrationError::LengthMismatch,
+        WeightError::Invalid => RegistrationError::InvalidWeights,
+    })?;
+    // points of zero weight do not constrain the transform
+    let used = (0..src.len())
+        .filter(|&i| weight(weights, i) > T::zero())
+        .count();
+    if used < min_points {
+        return Err(RegistrationError::TooFewPoints);
+    }
+    let (mut src_mean, mut dst_mean) = (Vector3::zeros(), Vector3::zeros());
+    if centered {
+        for (i, (a, b)) in src.iter().zip(dst).enumerate() {
+            let w = weight(weights, i) / sum;
+            src_mean += a.val * w;
+            dst_mean += b.val * w;
+        }
+    }
+    let mut cross = Matrix3::zeros();
+    let mut src_var = T::zero();
+    for (i, (a, b)) in src.iter().zip(dst).enumerate() {
+        let w = weight(weights, i) / sum;
+        let a = a.val - src_mean;
+        let b = b.val - dst_mean;
+        cross += a * b.transpose() * w;
+        src_var += a.dot(&a) * w;
+    }
+    Ok(Moments {
+        src_mean,
+        dst_mean,
+        cross,
+        src_var,
+    })
+}
+
+/// relative tolerance of the degeneracy checks
+fn tolerance<T: Real>() -> T {
+    T::epsilon().sqrt()
+}
+
+fn abs_max<T: Real>(a: T, b: T) -> T {
+    a.abs().max(b.abs())
+}
+
+/// rotation maximizing `tr(R H)` for the cross covariance `H = Σ srcᵢ dstᵢᵀ`,
+/// with the trace of the singular values after the reflection correction
+pub(crate) fn svd_rotation<T: Real + RealField>(
+    cross: &Matrix3<T>,
+) -> Result<(Matrix3<T>, T), RegistrationError> {
+    let svd = cross.svd(true, true);
+    let (u, v_t) = (svd.u.unwrap(), svd.v_t.unwrap());
+    let s = svd.singular_values;
+    // a unique rotation needs a cross covariance of rank 2 at least
+    let tol = tolerance::<T>();
+    if s[0] <= T::zero() || s[1] <= tol * s[0] {
+        return Err(RegistrationError::Degenerate);
+    }
+    let mut d = Matrix3::identity();
+    let mut trace = s[0] + s[1] + s[2];
+    if (u * v_t).determinant() < T::zero() {
+        d[(2, 2)] = -T::one();
+        trace -= s[2] + s[2];
+    }
+    Ok((v_t.transpose() * d * u.transpose(), trace))
+}
+
+/// Kabsch rotation about the origin, the points are not centered
+///
+/// requires at least 2 points which are not collinear with the origin
+pub fn kabsch<T: Real + RealField>(
+    src: &[Point<T>],
+    dst: &[Point<T>],
+    weights: Option<&[T]>,
+) -> Result<SO3<T>, RegistrationError> {
+    let m = moments(src, dst, weights, false, 2)?;
+    let (r, _) = svd_rotation(&m.cross)?;
+    Ok(SO3 { val: r })
+}
+
+/// rigid transform by the SVD method of Umeyama (Kabsch on the centered points)
+///
+/// requires at least 3 points which are not collinear
+pub fn umeyama<T: Real + RealField>(
+    src: &[Point<T>],
+    dst: &[Point<T>],
+    weights: Option<&[T]>,
+) -> Result<SE3<T>, RegistrationError> {
+    let m = moments(src, dst, weights, true, 3)?;
+    let (r, _) = svd_rotation(&m.cross)?;
+    Ok(SE3::from_rp(&r, &(m.dst_mean - r * m.src_mean)))
+}
+
+/// similarity transform by the method of Umeyama
+///
+/// requires at least 3 points which are not collinear
+pub fn umeyama_similarity<T: Real + RealField>(
+    src: &[Point<T>],
+    dst: &[Point<T>],
+    weights: Option<&[T]>,
+) -> Result<Similarity<T>, RegistrationError> {
+    let m = moments(src, dst, weights, true, 3)?;
+    let (r, trace) = svd_rotation(&m.cross)?;
+    let scale = trace / m.src_var;
+    let t = m.dst_mean - r * m.src_mean * scale;
+    Ok(Similarity {
+        pose: SE3::from_rp(&r, &t),
+        scale,
+    })
+}
+
+/// rigid transform by the unit quaternion method of Horn
+///
+/// requires at least 3 points which are not collinear
+pub fn horn<T: Real + RealField>(
+    src: &[Point<T>],
+    dst: &[Point<T>],
+    weights: Option<&[T]>,
+) -> Result<SE3<T>, RegistrationError> {
+    let m = moments(src, dst, weights, true, 3)?;
+    let s = |i: usize, j: usize| m.cross[(i, j)];
+    let (xx, xy, xz) = (s(0, 0), s(0, 1), s(0, 2));
+    let (yx, yy, yz) = (s(1, 0), s(1, 1), s(1, 2));
+    let (zx, zy, zz) = (s(2, 0), s(2, 1), s(2, 2));
+    #[rustfmt::skip]
+    let n = Matrix4::new(
+        xx + yy + zz, yz - zy,       zx - xz,       xy - yx,
+        yz - zy,      xx - yy - zz,  xy + yx,       zx + xz,
+        zx - xz,      xy + yx,       -xx + yy - zz, yz + zy,
+        xy - yx,      zx + xz,       yz + zy,       -xx - yy + zz,
+    );
+    let eigen = SymmetricEigen::new(n);
+    let mut order = [0, 1, 2, 3];
+    order.sort_by(|&a, &b| {
+        eigen.eigenvalues[b]
+            .partial_cmp(&eigen.eigenvalues[a])
+            .unwrap_or(core::cmp::Ordering::Equal)
+    });
+    // the largest eigenvalue must be simple for a unique rotation
+    let (l0, l1) = (eigen.eigenvalues[order[0]], eigen.eigenvalues[order[1]]);
+    let scale = abs_max(l0, eigen.eigenvalues[order[3]]);
+    let tol = tolerance::<T>();
+    if scale <= T::zero() || l0 - l1 <= tol * scale {
+        return Err(RegistrationError::Degenerate);
+    }
+    let q = eigen.eigenvectors.column(order[0]);
+    let r = quat_to_rot(&[q[1], q[2], q[3], q[0]]);
+    Ok(SE3::from_rp(&r, &(m.dst_mean - r * m.src_mean)))
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::Group;
+
+    use super::*;
+
+    fn points() -> [Point<f64>; 6] {
+        [
+            Point::new(0., 0., 0.),
+            Point::new(1., 0.2, -0.3),
+            Point::new(-0.5, 1., 0.4),
+            Point::new(0.3, -0.7, 1.),
+            Point::new(2., 1., 1.),
+            Point::new(-1., -1., 0.5),
+        ]
+    }
+
+    fn pose() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(0.4, -1.2, 2.5), [1., -2., 3.])
+    }
+
+    #[test]
+    fn test_rigid() {
+        let src = points();
+        let dst = src.clone().map(|p| pose().act(&p));
+        let weights = [1., 2., 0.5, 1., 3., 0.];
+        for weights in [None, Some(&weights[..])] {
+            assert_relative_eq!(
+                umeyama(&src, &dst, weights).unwrap(),
+                pose(),
+                epsilon = 1e-12
+            );
+            assert_relative_eq!(horn(&src, &dst, weights).unwrap(), pose(), epsilon = 1e-12);
+        }
+
+        let rot = SO3::from_euler_angles(0.4, -1.2, 2.5);
+        let dst = src.clone().map(|p| rot.act(&p));
+        assert_relative_eq!(
+            kabsch(&src[1..3], &dst[1..3], None).unwrap(),
+            rot,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        let src = points();
+        let sim = Similarity {
+            pose: pose(),
+            scale: 2.5,
+        };
+        let dst = src.clone().map(|p| sim.act(&p));
+        let sim_ = umeyama_similarity(&src, &dst, None).unwrap();
+        assert_relative_eq!(sim_.pose, sim.pose, epsilon = 1e-12);
+        assert_relative_eq!(sim_.scale, sim.scale, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_noise_and_reflection() {
+        let src = points();
+        let noise = [0.01, -0.02, 0.015, 0., -0.01, 0.02];
+        let dst: Vec<_> = src
+            .iter()
+            .zip(noise)
+            .map(|(p, n)| {
+                let q = pose().act(p);
+                Point::new(q.x() + n, q.y() - n, q.z() + n * 0.5)
+            })
+            .collect();
+        let a = umeyama(&src, &dst, None).unwrap();
+        let b = horn(&src, &dst, None).unwrap();
+        assert_relative_eq!(a, b, epsilon = 1e-10);
+        assert_relative_eq!(a, pose(), epsilon = 0.05);
+
+        // the best proper rotation of mirrored points
+        let dst = src.clone().map(|p| Point::new(-p.x(), p.y(), p.z()));
+        let r = umeyama(&src, &dst, None).unwrap().rp().0;
+        assert_relative_eq!(r.determinant(), 1., epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_errors() {
+        let src = points();
+        let line = [0., 1., 2., 3.].map(|t| Point::new(t, 2. * t, -t));
+        let dst = line.clone().map(|p| pose().act(&p));
+        assert_eq!(
+            umeyama(&line, &dst, None),
+            Err(RegistrationError::Degenerate)
+        );
+        assert_eq!(horn(&line, &dst, None), Err(RegistrationError::Degenerate));
+        assert_eq!(
+            umeyama_similarity(&line, &dst, None),
+            Err(RegistrationError::Degenerate)
+        );
+        let same = [
+            Point::new(1., 1., 1.),
+            Point::new(1., 1., 1.),
+            Point::new(1., 1., 1.),
+        ];
+        assert_eq!(
+            umeyama(&same, &same, None),
+            Err(RegistrationError::Degenerate)
+        );
+
+        assert_eq!(
+            umeyama(&src[..2], &src[..2], None),
+            Err(RegistrationError::TooFewPoints)
+        );
+        assert_eq!(
+            umeyama(&src[..3], &src[..3], Some(&[1., 1., 0.])),
+            Err(RegistrationError::TooFewPoints)
+        );
+        assert_eq!(
+            umeyama(&src, &src[..3], None),
+            Err(RegistrationError::LengthMismatch)
+        );
+        assert_eq!(
+            kabsch(&src, &src, Some(&[1.])),
+            Err(RegistrationError::LengthMismatch)
+        );
+        assert_eq!(
+            horn(&src[..3], &src[..3], Some(&[1., f64::NAN, 1.])),
+            Err(RegistrationError::InvalidWeights)
+        );
+    }
+}
@@ -2,7 +2,7 @@ use core::fmt::{Debug, Display};
 
 use nalgebra::Matrix6;
 
-use crate::{Adjoint, Real};
+use crate::{Adjoint, Real, SimdReal};
 
 use super::se3;
 
@@ -37,7 +37,7 @@ impl<T> AsRef<Matrix6<T>> for AdjSE3<T> {
 
 impl<T> AdjSE3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     /// Create a new AdjSE3 from a slice without checking the contents
     ///
@@ -64,7 +64,7 @@ where
 
 impl<T> Adjoint for AdjSE3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Algebra = se3<T>;
 
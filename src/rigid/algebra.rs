@@ -3,12 +3,11 @@ use core::{
     ops::{Add, Mul, Sub},
 };
 
-use nalgebra::{Matrix3, Matrix4, Vector3, Vector6};
+use nalgebra::{Matrix3, Vector3, Vector6};
 
 use crate::{
-    so3,
-    utils::{approx_zero, axis_angle, hat, hat_se3},
-    Algebra, Real,
+    utils::{hat, hat_se3, length_squared, so3_coefs},
+    Algebra, Real, SimdReal,
 };
 
 use super::{Vec6, SE3};
@@ -30,7 +29,7 @@ pub struct se3<T> {
 
 impl<T> se3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     /// Create a new se3 from rotation and translation
     pub fn new(r: [T; 3], p: [T; 3]) -> Self {
@@ -58,7 +57,7 @@ where
 
 impl<T> Mul<T> for se3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Self;
 
@@ -71,7 +70,7 @@ where
 
 impl<T> Mul<&T> for se3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Self;
 
@@ -84,7 +83,7 @@ where
 
 impl<T> Add for se3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Self;
 
@@ -97,7 +96,7 @@ where
 
 impl<T> Sub for se3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Self;
 
@@ -110,32 +109,23 @@ where
 
 impl<T> Algebra for se3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Group = SE3<T>;
     type Vector = Vec6<T>;
 
+    const DIM: usize = 6;
+
     fn exp(&self) -> Self::Group {
         let vec = self.vee();
         let v = Vector3::from_column_slice(&vec.p());
         let w = Vector3::from_column_slice(&vec.r());
-        let (axis, theta) = axis_angle(&w);
-        if approx_zero(theta) {
-            let mut res = Matrix4::identity();
-            res.view_mut((0, 3), (3, 1)).copy_from(&v);
-            SE3 { val: res }
-        } else {
-            let mut res = Matrix4::identity();
-            let w_so3 = hat(&axis);
-            let vv = Matrix3::identity() * theta
-                + w_so3 * (T::one() - theta.cos())
-                + w_so3 * w_so3 * (theta - theta.sin());
-            let rot = so3 { val: w }.exp();
-            let rot = rot.val;
-            res.view_mut((0, 0), (3, 3)).copy_from(&rot);
-            res.view_mut((0, 3), (3, 1)).copy_from(&(vv * v / theta));
-            SE3 { val: res }
-        }
+        let (a, b, c) = so3_coefs(length_squared(&w));
+        let w_so3 = hat(&w);
+        let w_so3_2 = w_so3 * w_so3;
+        let rot = Matrix3::identity() + w_so3 * a + w_so3_2 * b;
+        let vv = Matrix3::identity() + w_so3 * b + w_so3_2 * c;
+        SE3::from_rp(&rot, &(vv * v))
     }
 
     fn vee(&self) -> Self::Vector {
@@ -164,11 +154,9 @@ mod test {
         let se3 = Vec6::new([0., 0., 1.], [1., 0., 0.]).hat() * FRAC_PI_2;
         let v = se3.exp();
 
-        let s = SE3 {
-            val: Matrix4::new(
-                0., -1., 0., 1., 1., 0., 0., 1., 0., 0., 1., 0., 0., 0., 0., 1.,
-            ),
-        };
-        assert_relative_eq!(v.val, s.val);
+        let s = Matrix4::new(
+            0., -1., 0., 1., 1., 0., 0., 1., 0., 0., 1., 0., 0., 0., 0., 1.,
+        );
+        assert_relative_eq!(v.matrix(), s);
     }
 }
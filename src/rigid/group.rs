@@ -3,18 +3,16 @@ use core::{
     ops::{Mul, MulAssign},
 };
 
-use nalgebra::{Matrix3, Matrix4, Matrix6, Vector3, Vector4, Vector6};
+use nalgebra::{Matrix3, Matrix4, Matrix6, Vector3, Vector6};
 
-use crate::{
-    utils::{approx_zero_vec, axis_angle, hat},
-    Group, Point, Real, SO3,
-};
+use crate::{utils::hat, Algebra, Group, Point, Real, SimdReal, SO3};
 
 use super::{se3, AdjSE3};
 
 /// SE3 group, rotation and translation in 3D space
 ///
-/// SE3 is a 4x4 matrix
+/// SE3 is stored as its rotation and translation blocks,
+/// [SE3::matrix] gives the 4x4 homogeneous matrix
 /// ```ignore
 /// SE3 = [
 ///  R t
@@ -22,7 +20,8 @@ use super::{se3, AdjSE3};
 /// ]
 #[derive(Debug)]
 pub struct SE3<T> {
-    pub(crate) val: Matrix4<T>,
+    pub(crate) rot: SO3<T>,
+    pub(crate) trans: Vector3<T>,
 }
 
 impl<T> Display for SE3<T>
@@ -30,19 +29,19 @@ where
     T: Display + Real,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        self.val.fmt(f)
+        self.matrix().fmt(f)
     }
 }
 
-impl<T> AsRef<Matrix4<T>> for SE3<T> {
-    fn as_ref(&self) -> &Matrix4<T> {
-        &self.val
+impl<T: SimdReal> From<SE3<T>> for Matrix4<T> {
+    fn from(t: SE3<T>) -> Self {
+        t.matrix()
     }
 }
 
 impl<T> SE3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     /// Create a new SE3 from a SO3 and translation
     pub fn new(rot: &SO3<T>, p: [T; 3]) -> Self {
@@ -52,141 +51,139 @@ where
     /// Create a new identity SE3 group
     pub fn identity() -> Self {
         Self {
-            val: Matrix4::identity(),
+            rot: SO3::identity(),
+            trans: Vector3::zeros(),
         }
     }
 
     /// create SO3 and translation from SE3
     pub fn rot_trans(&self) -> (SO3<T>, [T; 3]) {
-        let (r, p) = self.rp();
-        (SO3 { val: r }, p.into())
+        (self.rot.clone(), self.trans.into())
+    }
+
+    /// 4x4 homogeneous matrix
+    pub fn matrix(&self) -> Matrix4<T> {
+        let mut val = Matrix4::identity();
+        val.fixed_view_mut::<3, 3>(0, 0).copy_from(&self.rot.val);
+        val.fixed_view_mut::<3, 1>(0, 3).copy_from(&self.trans);
+        val
     }
 }
 
 impl<T> SE3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
-    fn rp(&self) -> (Matrix3<T>, Vector3<T>) {
-        let r = self.val.fixed_view::<3, 3>(0, 0);
-        let p = self.val.fixed_view::<3, 1>(0, 3);
-        (r.into(), p.into())
+    pub(crate) fn rp(&self) -> (Matrix3<T>, Vector3<T>) {
+        (self.rot.val, self.trans)
     }
 
-    fn from_rp(r: &Matrix3<T>, p: &Vector3<T>) -> Self {
-        let mut val = Matrix4::identity();
-        val.fixed_view_mut::<3, 3>(0, 0).copy_from(r);
-        val.fixed_view_mut::<3, 1>(0, 3).copy_from(p);
-        Self { val }
+    pub(crate) fn from_rp(r: &Matrix3<T>, p: &Vector3<T>) -> Self {
+        Self {
+            rot: SO3 { val: *r },
+            trans: *p,
+        }
     }
 }
 
 impl<T> Group for SE3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
+    const DOF: usize = 6;
+
+    fn identity() -> Self {
+        Self::identity()
+    }
+
     type Algebra = se3<T>;
 
     fn log(&self) -> Self::Algebra {
-        let (r, p) = self.rp();
-        let w = SO3 { val: r }.log().val;
-
-        if approx_zero_vec(&w) {
-            let mut res = Vector6::zeros();
-            res.view_mut((3, 0), (3, 1)).copy_from(&p);
-            Self::Algebra { val: res }
-        } else {
-            let two = T::one() + T::one();
-            let (w_, theta) = axis_angle(&w);
-            let w_so3 = hat(&w_);
-            let v = {
-                let a = Matrix3::identity() / theta;
-                let b = w_so3 / two;
-                let c = T::one() / theta - T::one() / (theta / two).tan() / two; // * w_so3 * w_so3;
-                let c = w_so3 * w_so3 * c;
-                (a - b + c) * p
-            };
-            let mut res = Vector6::zeros();
-            res.view_mut((0, 0), (3, 1)).copy_from(&w);
-            res.view_mut((3, 0), (3, 1)).copy_from(&(v * theta));
-            Self::Algebra { val: res }
-        }
+        let w = self.rot.log().vee();
+        // V^-1 is the inverse left Jacobian of SO3
+        let v = w.ljac_inv() * self.trans;
+        let mut res = Vector6::zeros();
+        res.fixed_rows_mut::<3>(0).copy_from(&w.val);
+        res.fixed_rows_mut::<3>(3).copy_from(&v);
+        Self::Algebra { val: res }
     }
 
     type Adjoint = AdjSE3<T>;
 
     fn adjoint(&self) -> Self::Adjoint {
-        let (r, p) = self.rp();
-        let p_so3 = hat(&p);
+        let r = self.rot.val;
         let mut res = Matrix6::zeros();
-        res.view_mut((0, 0), (3, 3)).copy_from(&r);
-        res.view_mut((3, 0), (3, 3)).copy_from(&(p_so3 * r));
-        res.view_mut((3, 3), (3, 3)).copy_from(&r);
+        res.fixed_view_mut::<3, 3>(0, 0).copy_from(&r);
+        res.fixed_view_mut::<3, 3>(3, 0)
+            .copy_from(&(hat(&self.trans) * r));
+        res.fixed_view_mut::<3, 3>(3, 3).copy_from(&r);
         Self::Adjoint { val: res }
     }
 
     fn inv(&self) -> Self {
-        let (r, p) = self.rp();
-        Self::from_rp(&r.transpose(), &(-r.transpose() * p))
+        let rt = self.rot.val.transpose();
+        Self {
+            trans: -(rt * self.trans),
+            rot: SO3 { val: rt },
+        }
     }
 
     fn mat_mul(&self, other: &Self) -> Self {
-        SE3 {
-            val: self.val * other.val,
+        Self {
+            rot: SO3 {
+                val: self.rot.val * other.rot.val,
+            },
+            trans: self.rot.val * other.trans + self.trans,
         }
     }
 
     type Point = Point<T>;
 
     fn act(&self, other: &Self::Point) -> Self::Point {
-        let p4 = self.val * Vector4::new(other.val.x, other.val.y, other.val.z, T::one());
         Point {
-            val: Vector3::new(p4.x, p4.y, p4.z),
+            val: self.rot.val * other.val + self.trans,
         }
     }
 }
 
-impl<T: Real> Mul<T> for SE3<T> {
+/// scales the rotation and translation blocks, the last row stays `[0, 0, 0, 1]`
+impl<T: SimdReal> Mul<T> for SE3<T> {
     type Output = SE3<T>;
 
     fn mul(self, rhs: T) -> Self::Output {
-        SE3 {
-            val: self.val * rhs,
-        }
+        &self * rhs
     }
 }
 
-impl<T: Real> Mul<T> for &SE3<T> {
+/// scales the rotation and translation blocks, the last row stays `[0, 0, 0, 1]`
+impl<T: SimdReal> Mul<T> for &SE3<T> {
     type Output = SE3<T>;
 
     fn mul(self, rhs: T) -> Self::Output {
-        SE3 {
-            val: self.val * rhs,
-        }
+        let (r, p) = self.rp();
+        SE3::from_rp(&(r * rhs), &(p * rhs))
     }
 }
 
-impl<T: Real> Mul<&T> for SE3<T> {
+/// scales the rotation and translation blocks, the last row stays `[0, 0, 0, 1]`
+impl<T: SimdReal> Mul<&T> for SE3<T> {
     type Output = SE3<T>;
 
     fn mul(self, rhs: &T) -> Self::Output {
-        SE3 {
-            val: self.val * *rhs,
-        }
+        &self * *rhs
     }
 }
 
-impl<T: Real> Mul<&T> for &SE3<T> {
+/// scales the rotation and translation blocks, the last row stays `[0, 0, 0, 1]`
+impl<T: SimdReal> Mul<&T> for &SE3<T> {
     type Output = SE3<T>;
 
     fn mul(self, rhs: &T) -> Self::Output {
-        SE3 {
-            val: self.val * *rhs,
-        }
+        self * *rhs
     }
 }
 
-impl<T: Real> Mul<Point<T>> for SE3<T> {
+impl<T: SimdReal> Mul<Point<T>> for SE3<T> {
     type Output = Point<T>;
 
     fn mul(self, rhs: Point<T>) -> Self::Output {
@@ -194,7 +191,7 @@ impl<T: Real> Mul<Point<T>> for SE3<T> {
     }
 }
 
-impl<T: Real> Mul<Point<T>> for &SE3<T> {
+impl<T: SimdReal> Mul<Point<T>> for &SE3<T> {
     type Output = Point<T>;
 
     fn mul(self, rhs: Point<T>) -> Self::Output {
@@ -202,7 +199,7 @@ impl<T: Real> Mul<Point<T>> for &SE3<T> {
     }
 }
 
-impl<T: Real> Mul<&Point<T>> for SE3<T> {
+impl<T: SimdReal> Mul<&Point<T>> for SE3<T> {
     type Output = Point<T>;
 
     fn mul(self, rhs: &Point<T>) -> Self::Output {
@@ -210,7 +207,7 @@ impl<T: Real> Mul<&Point<T>> for SE3<T> {
     }
 }
 
-impl<T: Real> Mul<&Point<T>> for &SE3<T> {
+impl<T: SimdReal> Mul<&Point<T>> for &SE3<T> {
     type Output = Point<T>;
 
     fn mul(self, rhs: &Point<T>) -> Self::Output {
@@ -218,7 +215,7 @@ impl<T: Real> Mul<&Point<T>> for &SE3<T> {
     }
 }
 
-impl<T: Real> Mul<SE3<T>> for SE3<T> {
+impl<T: SimdReal> Mul<SE3<T>> for SE3<T> {
     type Output = SE3<T>;
 
     fn mul(self, rhs: SE3<T>) -> Self::Output {
@@ -226,7 +223,7 @@ impl<T: Real> Mul<SE3<T>> for SE3<T> {
     }
 }
 
-impl<T: Real> Mul<&SE3<T>> for SE3<T> {
+impl<T: SimdReal> Mul<&SE3<T>> for SE3<T> {
     type Output = SE3<T>;
 
     fn mul(self, rhs: &SE3<T>) -> Self::Output {
@@ -234,7 +231,7 @@ impl<T: Real> Mul<&SE3<T>> for SE3<T> {
     }
 }
 
-impl<T: Real> MulAssign<SE3<T>> for SE3<T> {
+impl<T: SimdReal> MulAssign<SE3<T>> for SE3<T> {
     fn mul_assign(&mut self, rhs: SE3<T>) {
         *self = self.clone() * rhs;
     }
@@ -246,25 +243,19 @@ mod test {
 
     use approx::assert_relative_eq;
 
-    use crate::Algebra;
+    use crate::{Algebra, Vec6};
 
     use super::*;
 
     #[test]
     fn test_new() {
-        let se3 = SE3::<f64> {
-            val: Matrix4::identity(),
-        };
-        assert_eq!(se3.val, Matrix4::identity());
+        let se3 = SE3::<f64>::new(&SO3::identity(), [0., 0., 0.]);
+        assert_eq!(se3.matrix(), Matrix4::identity());
     }
 
     #[test]
     fn test_rp() {
-        let se3 = SE3 {
-            val: Matrix4::new(
-                1., 0., 0., 1., 0., 1., 0., 2., 0., 0., 1., 3., 0., 0., 0., 1.,
-            ),
-        };
+        let se3 = SE3::new(&SO3::identity(), [1., 2., 3.]);
         let (r, p) = se3.rp();
         assert_eq!(r, Matrix3::new(1., 0., 0., 0., 1., 0., 0., 0., 1.));
         assert_eq!(p, Vector3::new(1., 2., 3.));
@@ -277,7 +268,7 @@ mod test {
             &Vector3::new(1., 2., 3.),
         );
         assert_eq!(
-            se3.val,
+            se3.matrix(),
             Matrix4::new(1., 0., 0., 1., 0., 1., 0., 2., 0., 0., 1., 3., 0., 0., 0., 1.)
         );
     }
@@ -293,6 +284,45 @@ mod test {
         assert_relative_eq!(se3.val, Vector6::new(0., 0., 1., 1., 0., 0.) * FRAC_PI_2);
     }
 
+    /// maximum error of log(exp) relative to machine epsilon,
+    /// and of exp relative to the f64 result
+    fn exp_log_errors<T: Real>() -> (f64, f64) {
+        use crate::utils::test::{axes, sweep};
+        let eps = T::epsilon().to_f64().unwrap();
+        let (mut exp_err, mut log_err) = (0f64, 0f64);
+        for axis in axes() {
+            for theta in sweep() {
+                let mut xi = Vector6::new(0., 0., 0., 1., -2., 0.5);
+                xi.fixed_rows_mut::<3>(0).copy_from(&(axis * theta));
+                let reference = se3 { val: xi }.exp();
+                let xi = xi.map(|x| T::from(x).unwrap());
+                let t = se3 { val: xi }.exp();
+                let err = (t.matrix().map(|x| x.to_f64().unwrap()) - reference.matrix())
+                    .abs()
+                    .max();
+                exp_err = exp_err.max(err / eps);
+
+                // f32 rounds π - 1e-9 above π, log then returns the antipodal vector
+                if T::from(theta).unwrap() < T::PI() {
+                    let xi_ = t.log().val;
+                    let err = (xi_ - xi).map(|x| x.to_f64().unwrap()).abs().max();
+                    let scale = xi.map(|x| x.to_f64().unwrap()).abs().max();
+                    log_err = log_err.max(err / eps / scale);
+                }
+            }
+        }
+        (exp_err, log_err)
+    }
+
+    #[test]
+    fn test_exp_log_sweep() {
+        let (_, log_err) = exp_log_errors::<f64>();
+        assert!(log_err <= 8., "{log_err}");
+        let (exp_err, log_err) = exp_log_errors::<f32>();
+        assert!(exp_err <= 8., "{exp_err}");
+        assert!(log_err <= 8., "{log_err}");
+    }
+
     #[test]
     fn test_adjoint() {
         let se3 = SE3::from_rp(
@@ -309,12 +339,40 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_plus_minus() {
+        fn round_trip<G>(g: &G, tau: &<G::Algebra as Algebra>::Vector) -> G
+        where
+            G: Group,
+        {
+            let h = g.plus(tau);
+            g.plus(&h.minus(g))
+        }
+        let t = SE3::new(&SO3::from_euler_angles(0.1, 0.2, 0.3), [1., 2., 3.]);
+        let tau = Vec6::new([0.3, -0.2, 0.1], [-1., 0.5, 2.]);
+        assert_relative_eq!(t.plus(&tau).minus(&t), tau, epsilon = 1e-12);
+        assert_relative_eq!(round_trip(&t, &tau), t.plus(&tau), epsilon = 1e-12);
+        assert_relative_eq!(t.between(&t), <SE3<f64> as Group>::identity());
+        assert_eq!(SE3::<f64>::DOF, 6);
+    }
+
     #[test]
     fn test_inv() {
         let se3 = SE3::from_rp(
             &Matrix3::new(0., -1., 0., 1., 0., 0., 0., 0., 1.),
             &Vector3::new(1., 1., 0.),
         );
-        assert_relative_eq!(se3.mat_mul(&se3.inv()).val, Matrix4::identity());
+        assert_relative_eq!(se3.mat_mul(&se3.inv()).matrix(), Matrix4::identity());
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_scale() {
+        let se3 = SE3::new(&SO3::from_euler_angles(0.1, 0.2, 0.3), [1., 2., 3.]);
+        let mut expected = se3.matrix() * 2.;
+        expected[(3, 3)] = 1.;
+        assert_eq!((&se3 * 2.).matrix(), expected);
+        assert_eq!(&se3 * &2., se3.clone() * 2.);
+        assert_eq!(se3.clone() * &2., se3 * 2.);
     }
 }
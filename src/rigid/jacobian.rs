@@ -0,0 +1,358 @@
+use nalgebra::{Matrix3, Matrix3x6, Matrix6, Vector3};
+
+use crate::{
+    utils::{hat, length_squared, se3_jac_coefs, set_jac, so3_coefs},
+    Algebra, Group, Perturbation, Point, Real, Vec3, Vector,
+};
+
+use super::{Vec6, SE3};
+
+impl<T> Vec6<T>
+where
+    T: Real,
+{
+    fn w(&self) -> Vector3<T> {
+        self.val.fixed_rows::<3>(0).into()
+    }
+
+    fn v(&self) -> Vector3<T> {
+        self.val.fixed_rows::<3>(3).into()
+    }
+
+    fn neg(&self) -> Self {
+        Self { val: -self.val }
+    }
+
+    /// translation block of the left Jacobian
+    fn q(&self) -> Matrix3<T> {
+        let w = self.w();
+        let theta2 = length_squared(&w);
+        let (_, _, a) = so3_coefs(theta2);
+        let (b, c) = se3_jac_coefs(theta2);
+        let w = hat(&w);
+        let v = hat(&self.v());
+        let half = T::one() / (T::one() + T::one());
+        let three = T::one() + T::one() + T::one();
+        let wv = w * v;
+        let vw = v * w;
+        let wvw = wv * w;
+        let ww = w * w;
+        v * half
+            + (wv + vw + wvw) * a
+            + (ww * v + vw * w - wvw * three) * b
+            + (wvw * w + w * wvw) * c
+    }
+
+    /// left Jacobian of SE3
+    /// ```text
+    /// exp((v + δ)^) ≈ exp((Jl δ)^) * exp(v^)
+    /// ```
+    pub fn ljac(&self) -> Matrix6<T> {
+        let jl = Vec3 { val: self.w() }.ljac();
+        let mut res = Matrix6::zeros();
+        res.fixed_view_mut::<3, 3>(0, 0).copy_from(&jl);
+        res.fixed_view_mut::<3, 3>(3, 0).copy_from(&self.q());
+        res.fixed_view_mut::<3, 3>(3, 3).copy_from(&jl);
+        res
+    }
+
+    /// right Jacobian of SE3
+    /// ```text
+    /// exp((v + δ)^) ≈ exp(v^) * exp((Jr δ)^)
+    /// ```
+    pub fn rjac(&self) -> Matrix6<T> {
+        self.neg().ljac()
+    }
+
+    /// inverse of the left Jacobian of SE3
+    pub fn ljac_inv(&self) -> Matrix6<T> {
+        let jl_inv = Vec3 { val: self.w() }.ljac_inv();
+        let mut res = Matrix6::zeros();
+        res.fixed_view_mut::<3, 3>(0, 0).copy_from(&jl_inv);
+        res.fixed_view_mut::<3, 3>(3, 0)
+            .copy_from(&(-jl_inv * self.q() * jl_inv));
+        res.fixed_view_mut::<3, 3>(3, 3).copy_from(&jl_inv);
+        res
+    }
+
+    /// inverse of the right Jacobian of SE3
+    pub fn rjac_inv(&self) -> Matrix6<T> {
+        self.neg().ljac_inv()
+    }
+
+    /// exp map, `jac` is the right Jacobian
+    pub fn exp_jac(&self, jac: Option<&mut Matrix6<T>>) -> SE3<T> {
+        set_jac(jac, || self.rjac());
+        self.hat().exp()
+    }
+}
+
+/// Operations with optional Jacobians.
+///
+/// All Jacobians are defined on the right (local) tangent space,
+/// `J = d(f(X ⊕ δ) ⊖ f(X)) / dδ`, with `δ = [w, v]`.
+impl<T> SE3<T>
+where
+    T: Real,
+{
+    /// inverse, `X^-1`
+    pub fn inverse(&self, j_self: Option<&mut Matrix6<T>>) -> Self {
+        set_jac(j_self, || -self.adjoint().val);
+        self.inv()
+    }
+
+    /// composition, `X * Y`
+    pub fn compose(
+        &self,
+        other: &Self,
+        j_self: Option<&mut Matrix6<T>>,
+        j_other: Option<&mut Matrix6<T>>,
+    ) -> Self {
+        set_jac(j_self, || other.inv().adjoint().val);
+        set_jac(j_other, Matrix6::identity);
+        self.mat_mul(other)
+    }
+
+    /// right plus, `X ⊕ τ = X * exp(τ^)`
+    pub fn rplus(
+        &self,
+        tau: &Vec6<T>,
+        j_self: Option<&mut Matrix6<T>>,
+        j_tau: Option<&mut Matrix6<T>>,
+    ) -> Self {
+        let exp = tau.exp_jac(j_tau);
+        set_jac(j_self, || exp.inv().adjoint().val);
+        self.mat_mul(&exp)
+    }
+
+    /// left plus, `τ ⊕ X = exp(τ^) * X`
+    pub fn lplus(
+        &self,
+        tau: &Vec6<T>,
+        j_self: Option<&mut Matrix6<T>>,
+        j_tau: Option<&mut Matrix6<T>>,
+    ) -> Self {
+        set_jac(j_self, Matrix6::identity);
+        set_jac(j_tau, || self.inv().adjoint().val * tau.rjac());
+        tau.hat().exp().mat_mul(self)
+    }
+
+    /// right minus, `Y ⊖ X = log(X^-1 * Y)∨`, where `self` is `Y`
+    pub fn rminus(
+        &self,
+        other: &Self,
+        j_self: Option<&mut Matrix6<T>>,
+        j_other: Option<&mut Matrix6<T>>,
+    ) -> Vec6<T> {
+        let tau = self.minus(other);
+        set_jac(j_self, || tau.rjac_inv());
+        set_jac(j_other, || -tau.ljac_inv());
+        tau
+    }
+
+    /// left minus, `log(Y * X^-1)∨`, where `self` is `Y`
+    pub fn lminus(
+        &self,
+        other: &Self,
+        j_self: Option<&mut Matrix6<T>>,
+        j_other: Option<&mut Matrix6<T>>,
+    ) -> Vec6<T> {
+        let tau = self.mat_mul(&other.inv()).log().vee();
+        let jac = tau.rjac_inv() * other.adjoint().val;
+        set_jac(j_other, || -jac);
+        set_jac(j_self, || jac);
+        tau
+    }
+
+    /// log map, `jac` is the inverse right Jacobian
+    pub fn log_jac(&self, jac: Option<&mut Matrix6<T>>) -> Vec6<T> {
+        let tau = self.log().vee();
+        set_jac(jac, || tau.rjac_inv());
+        tau
+    }
+
+    /// action on point, `X * p`
+    pub fn act_jac(
+        &self,
+        point: &Point<T>,
+        j_self: Option<&mut Matrix3x6<T>>,
+        j_point: Option<&mut Matrix3<T>>,
+    ) -> Point<T> {
+        let (res, jac_self, jac_point) = self.act_with_jacobians(point, Perturbation::Right);
+        set_jac(j_self, || jac_self);
+        set_jac(j_point, || jac_point);
+        res
+    }
+
+    /// action on point, returns `X * p` and its Jacobians with respect to `X` and `p`
+    ///
+    /// ## Example
+    /// ```rust
+    /// use liealg::prelude::*;
+    /// use liealg::{Point, SE3, SO3};
+    /// let t = SE3::new(&SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3), [1., 2., 3.]);
+    /// let (p, j_t, j_p) = t.act_with_jacobians(&Point::new(1., 2., 3.), Perturbation::Right);
+    /// ```
+    pub fn act_with_jacobians(
+        &self,
+        point: &Point<T>,
+        perturbation: Perturbation,
+    ) -> (Point<T>, Matrix3x6<T>, Matrix3<T>) {
+        let (r, _) = self.rp();
+        let res = self.act(point);
+        let (j_w, j_v) = match perturbation {
+            Perturbation::Left => (-hat(&res.val), Matrix3::identity()),
+            Perturbation::Right => (-r * hat(&point.val), r),
+        };
+        let mut j_self = Matrix3x6::zeros();
+        j_self.fixed_view_mut::<3, 3>(0, 0).copy_from(&j_w);
+        j_self.fixed_view_mut::<3, 3>(0, 3).copy_from(&j_v);
+        (res, j_self, r)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::{SMatrix, SVector, Vector6};
+
+    use super::*;
+    use crate::SO3;
+
+    const EPS: f64 = 1e-6;
+
+    /// numerical Jacobian of `f` at zero perturbation
+    fn num_jac<F, const N: usize>(f: F) -> SMatrix<f64, N, 6>
+    where
+        F: Fn(&Vec6<f64>) -> SVector<f64, N>,
+    {
+        let f0 = f(&Vec6::new([0.; 3], [0.; 3]));
+        let mut jac = SMatrix::<f64, N, 6>::zeros();
+        for i in 0..6 {
+            let mut d = Vector6::zeros();
+            d[i] = EPS;
+            let df = (f(&Vec6 { val: d }) - f0) / EPS;
+            jac.set_column(i, &df);
+        }
+        jac
+    }
+
+    fn add(a: &Vec6<f64>, b: &Vec6<f64>) -> Vec6<f64> {
+        Vec6 { val: a.val + b.val }
+    }
+
+    fn x() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(0.3, -0.5, 1.2), [1., -2., 0.5])
+    }
+
+    fn y() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(-1.1, 0.2, 2.5), [-0.3, 0.7, 2.])
+    }
+
+    #[test]
+    fn test_jacobians() {
+        for v in [
+            Vec6::new([0.3, -0.2, 0.5], [1., 2., -3.]),
+            Vec6::new([1e-5, 0., -2e-5], [0.5, -1., 0.2]),
+            Vec6::new([0., 3., 0.1], [-1., 0., 1.]),
+        ] {
+            let exp = v.hat().exp();
+            let num = num_jac(|d| exp.inv().mat_mul(&add(&v, d).hat().exp()).log().val);
+            assert_relative_eq!(v.rjac(), num, epsilon = 1e-5);
+            let num = num_jac(|d| add(&v, d).hat().exp().mat_mul(&exp.inv()).log().val);
+            assert_relative_eq!(v.ljac(), num, epsilon = 1e-5);
+            assert_relative_eq!(v.rjac() * v.rjac_inv(), Matrix6::identity(), epsilon = 1e-9);
+            assert_relative_eq!(v.ljac() * v.ljac_inv(), Matrix6::identity(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_inverse_compose() {
+        let mut j = Matrix6::zeros();
+        let inv = x().inverse(Some(&mut j));
+        let num = num_jac(|d| inv.inv().mat_mul(&x().plus(d).inv()).log().val);
+        assert_relative_eq!(j, num, epsilon = 1e-5);
+
+        let (mut jx, mut jy) = (Matrix6::zeros(), Matrix6::zeros());
+        let xy = x().compose(&y(), Some(&mut jx), Some(&mut jy));
+        let num = num_jac(|d| x().plus(d).mat_mul(&y()).minus(&xy).val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| x().mat_mul(&y().plus(d)).minus(&xy).val);
+        assert_relative_eq!(jy, num, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_plus_minus() {
+        let tau = Vec6::new([0.3, -0.2, 0.5], [1., 2., -3.]);
+        let (mut jx, mut jt) = (Matrix6::zeros(), Matrix6::zeros());
+        let r = x().rplus(&tau, Some(&mut jx), Some(&mut jt));
+        assert_relative_eq!(r, x().plus(&tau));
+        let num = num_jac(|d| x().plus(d).plus(&tau).minus(&r).val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| x().plus(&add(&tau, d)).minus(&r).val);
+        assert_relative_eq!(jt, num, epsilon = 1e-5);
+
+        let l = x().lplus(&tau, Some(&mut jx), Some(&mut jt));
+        let num = num_jac(|d| tau.hat().exp().mat_mul(&x().plus(d)).minus(&l).val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| add(&tau, d).hat().exp().mat_mul(&x()).minus(&l).val);
+        assert_relative_eq!(jt, num, epsilon = 1e-5);
+
+        let (mut jy, mut jx) = (Matrix6::zeros(), Matrix6::zeros());
+        let tau = y().rminus(&x(), Some(&mut jy), Some(&mut jx));
+        let num = num_jac(|d| y().plus(d).minus(&x()).val - tau.val);
+        assert_relative_eq!(jy, num, epsilon = 1e-5);
+        let num = num_jac(|d| y().minus(&x().plus(d)).val - tau.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+
+        let tau = y().lminus(&x(), Some(&mut jy), Some(&mut jx));
+        let lminus = |a: &SE3<f64>, b: &SE3<f64>| a.mat_mul(&b.inv()).log().val;
+        let num = num_jac(|d| lminus(&y().plus(d), &x()) - tau.val);
+        assert_relative_eq!(jy, num, epsilon = 1e-5);
+        let num = num_jac(|d| lminus(&y(), &x().plus(d)) - tau.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_exp_log_act() {
+        let tau = Vec6::new([0.3, -0.2, 0.5], [1., 2., -3.]);
+        let mut j = Matrix6::zeros();
+        let r = tau.exp_jac(Some(&mut j));
+        assert_relative_eq!(j, tau.rjac());
+        let tau_ = r.log_jac(Some(&mut j));
+        assert_relative_eq!(tau_, tau, epsilon = 1e-12);
+        let num = num_jac(|d| r.plus(d).log().val - tau.val);
+        assert_relative_eq!(j, num, epsilon = 1e-5);
+
+        let p = Point::new(1., -2., 3.);
+        let (mut jx, mut jp) = (Matrix3x6::zeros(), Matrix3::zeros());
+        let q = x().act_jac(&p, Some(&mut jx), Some(&mut jp));
+        let num = num_jac(|d| x().plus(d).act(&p).val - q.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| {
+            let d = Vector3::new(d.val[3], d.val[4], d.val[5]);
+            x().act(&Point { val: p.val + d }).val - q.val
+        });
+        assert_relative_eq!(
+            jp,
+            Matrix3::from(num.fixed_view::<3, 3>(0, 3)),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_act_with_jacobians() {
+        let p = Point::new(1., -2., 3.);
+        let (q, jx, jp) = x().act_with_jacobians(&p, Perturbation::Right);
+        assert_relative_eq!(q, x().act(&p));
+        let num = num_jac(|d| x().plus(d).act(&p).val - q.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        assert_relative_eq!(jp, x().rp().0);
+
+        let (q, jx, jp) = x().act_with_jacobians(&p, Perturbation::Left);
+        assert_relative_eq!(q, x().act(&p));
+        let num = num_jac(|d| d.hat().exp().mat_mul(&x()).act(&p).val - q.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        assert_relative_eq!(jp, x().rp().0);
+    }
+}
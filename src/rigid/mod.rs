@@ -3,9 +3,12 @@
 mod adjoint;
 mod algebra;
 mod group;
+mod jacobian;
+mod uncertain;
 mod vector;
 
 pub use adjoint::AdjSE3;
 pub use algebra::se3;
 pub use group::SE3;
+pub use uncertain::UncertainSE3;
 pub use vector::Vec6;
@@ -0,0 +1,211 @@
+use nalgebra::{Matrix3, Matrix6};
+
+use crate::{
+    utils::{bracket, double_bracket},
+    Group, Perturbation, Propagation, Real,
+};
+
+use super::SE3;
+
+/// concentrated gaussian on SE3
+///
+/// ```ignore
+/// T = exp(ξ^) * mean, ξ ~ N(0, cov)
+/// ```
+/// the covariance is expressed on the left (global frame), as in Barfoot and Furgale,
+/// with the `[ω; v]` ordering of [Vec6](super::Vec6)
+#[derive(Debug)]
+pub struct UncertainSE3<T> {
+    pub(crate) mean: SE3<T>,
+    pub(crate) cov: Matrix6<T>,
+}
+
+/// rotation, translation-rotation and translation blocks of a covariance
+fn blocks<T: Real>(cov: &Matrix6<T>) -> (Matrix3<T>, Matrix3<T>, Matrix3<T>) {
+    (
+        cov.fixed_view::<3, 3>(0, 0).into(),
+        cov.fixed_view::<3, 3>(3, 0).into(),
+        cov.fixed_view::<3, 3>(3, 3).into(),
+    )
+}
+
+fn from_blocks<T: Real>(ww: &Matrix3<T>, vw: &Matrix3<T>, vv: &Matrix3<T>) -> Matrix6<T> {
+    let mut res = Matrix6::zeros();
+    res.fixed_view_mut::<3, 3>(0, 0).copy_from(ww);
+    res.fixed_view_mut::<3, 3>(3, 0).copy_from(vw);
+    res.fixed_view_mut::<3, 3>(0, 3).copy_from(&vw.transpose());
+    res.fixed_view_mut::<3, 3>(3, 3).copy_from(vv);
+    res
+}
+
+/// `𝒜` operator of the fourth order terms
+fn curly<T: Real>(cov: &Matrix6<T>) -> Matrix6<T> {
+    let (ww, vw, _) = blocks(cov);
+    let mut res = Matrix6::zeros();
+    let a = bracket(&ww);
+    res.fixed_view_mut::<3, 3>(0, 0).copy_from(&a);
+    res.fixed_view_mut::<3, 3>(3, 0)
+        .copy_from(&bracket(&(vw + vw.transpose())));
+    res.fixed_view_mut::<3, 3>(3, 3).copy_from(&a);
+    res
+}
+
+impl<T: Real> UncertainSE3<T> {
+    /// Create a new uncertain pose from its mean and its left covariance
+    pub fn new(mean: SE3<T>, cov: Matrix6<T>) -> Self {
+        Self { mean, cov }
+    }
+
+    /// Create a new uncertain pose with the covariance of a perturbation on the given side
+    pub fn with_perturbation(mean: SE3<T>, cov: Matrix6<T>, side: Perturbation) -> Self {
+        let cov = match side {
+            Perturbation::Left => cov,
+            Perturbation::Right => {
+                let ad = mean.adjoint().val;
+                ad * cov * ad.transpose()
+            }
+        };
+        Self { mean, cov }
+    }
+
+    /// mean pose
+    pub fn mean(&self) -> &SE3<T> {
+        &self.mean
+    }
+
+    /// covariance of the perturbation on the given side
+    pub fn cov(&self, side: Perturbation) -> Matrix6<T> {
+        match side {
+            Perturbation::Left => self.cov,
+            Perturbation::Right => {
+                let ad = self.mean.inv().adjoint().val;
+                ad * self.cov * ad.transpose()
+            }
+        }
+    }
+
+    /// compose two independent uncertain poses, `self * other`
+    pub fn compose(&self, other: &Self, order: Propagation) -> Self {
+        let ad = self.mean.adjoint().val;
+        let s1 = self.cov;
+        let s2 = ad * other.cov * ad.transpose();
+        let mut cov = s1 + s2;
+        if order == Propagation::FourthOrder {
+            let a1 = curly(&s1);
+            let a2 = curly(&s2);
+            let (ww1, vw1, vv1) = blocks(&s1);
+            let (ww2, vw2, vv2) = blocks(&s2);
+            let b_ww = double_bracket(&ww1, &ww2);
+            let b_vw =
+                double_bracket(&ww1, &vw2.transpose()) + double_bracket(&vw1.transpose(), &ww2);
+            let b_vv = double_bracket(&ww1, &vv2)
+                + double_bracket(&vw1.transpose(), &vw2)
+                + double_bracket(&vw1, &vw2.transpose())
+                + double_bracket(&vv1, &ww2);
+            let twelve = T::from(12).unwrap();
+            let four = T::from(4).unwrap();
+            cov += (a1 * s2 + s2 * a1.transpose() + a2 * s1 + s1 * a2.transpose()) / twelve
+                + from_blocks(&b_ww, &b_vw, &b_vv) / four;
+        }
+        Self {
+            mean: self.mean.mat_mul(&other.mean),
+            cov,
+        }
+    }
+
+    /// inverse, the covariance is propagated exactly
+    pub fn inverse(&self) -> Self {
+        let mean = self.mean.inv();
+        let ad = mean.adjoint().val;
+        Self {
+            cov: ad * self.cov * ad.transpose(),
+            mean,
+        }
+    }
+
+    /// relative pose `self⁻¹ * other` of two independent uncertain poses
+    pub fn between(&self, other: &Self, order: Propagation) -> Self {
+        self.inverse().compose(other, order)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::SO3;
+
+    use super::*;
+
+    fn pose(rpy: [f64; 3], p: [f64; 3]) -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(rpy[0], rpy[1], rpy[2]), p)
+    }
+
+    #[test]
+    fn test_compose_inverse() {
+        let t1 = pose([0.1, 0.2, 0.3], [1., 2., 3.]);
+        let t2 = pose([-0.5, 0.4, 1.2], [0., -1., 0.5]);
+        let c1 = Matrix6::from_diagonal(&nalgebra::Vector6::new(0.01, 0.02, 0.03, 0.1, 0.2, 0.3));
+        let u1 = UncertainSE3::new(t1.clone(), c1);
+        let u2 = UncertainSE3::new(t2.clone(), Matrix6::zeros());
+
+        // a certain second pose leaves the covariance unchanged
+        for order in [Propagation::SecondOrder, Propagation::FourthOrder] {
+            let u = u1.compose(&u2, order);
+            assert_relative_eq!(u.mean, t1.mat_mul(&t2));
+            assert_relative_eq!(u.cov, c1, epsilon = 1e-12);
+        }
+        // a certain first pose transports the covariance by its adjoint
+        let u = u2.compose(&u1, Propagation::FourthOrder);
+        let ad = t2.adjoint().val;
+        assert_relative_eq!(u.cov, ad * c1 * ad.transpose(), epsilon = 1e-12);
+
+        let inv = u1.inverse().inverse();
+        assert_relative_eq!(inv.mean, t1, epsilon = 1e-12);
+        assert_relative_eq!(inv.cov, c1, epsilon = 1e-12);
+        assert_relative_eq!(u1.inverse().cov(Perturbation::Right), c1, epsilon = 1e-12);
+
+        let rel = u1.between(&u1, Propagation::SecondOrder);
+        assert_relative_eq!(rel.mean, SE3::identity(), epsilon = 1e-12);
+
+        let right = UncertainSE3::with_perturbation(t1, c1, Perturbation::Right);
+        assert_relative_eq!(right.cov(Perturbation::Right), c1, epsilon = 1e-12);
+    }
+
+    /// compare with the covariance of sampled compositions
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_compose_monte_carlo() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        use crate::{distributions::Gaussian, Algebra, Vec6, Vector};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let t1 = pose([0.1, 0.2, 0.3], [1., 2., 3.]);
+        let t2 = pose([-0.5, 0.4, 1.2], [0., -1., 0.5]);
+        let c = Matrix6::from_diagonal(&nalgebra::Vector6::new(0.1, 0.1, 0.1, 0.2, 0.2, 0.2));
+        let u1 = UncertainSE3::new(t1.clone(), c);
+        let u2 = UncertainSE3::new(t2.clone(), c * 0.5);
+        let g1 = Gaussian::new([0.; 6], u1.cov).unwrap();
+        let g2 = Gaussian::new([0.; 6], u2.cov).unwrap();
+
+        let mean_inv = t1.mat_mul(&t2).inv();
+        let n = 50000;
+        let mut cov = Matrix6::zeros();
+        for _ in 0..n {
+            let x1: Vec6<f64> = rng.sample(g1);
+            let x2: Vec6<f64> = rng.sample(g2);
+            let t = x1
+                .hat()
+                .exp()
+                .mat_mul(&t1)
+                .mat_mul(&x2.hat().exp())
+                .mat_mul(&t2);
+            let xi = t.mat_mul(&mean_inv).log().vee().val;
+            cov += xi * xi.transpose() / n as f64;
+        }
+        let err2 = (u1.compose(&u2, Propagation::SecondOrder).cov - cov).norm();
+        let err4 = (u1.compose(&u2, Propagation::FourthOrder).cov - cov).norm();
+        assert!(err4 < 0.5 * err2, "{err4} {err2}");
+    }
+}
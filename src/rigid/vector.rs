@@ -5,7 +5,7 @@ use core::{
 
 use nalgebra::Vector6;
 
-use crate::{Real, Vector};
+use crate::{Real, SimdReal, Vector};
 
 use super::se3;
 
@@ -32,7 +32,7 @@ where
 
 impl<T> Mul<T> for Vec6<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Self;
 
@@ -45,7 +45,7 @@ where
 
 impl<T> Mul<T> for &Vec6<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Vec6<T>;
 
@@ -58,7 +58,7 @@ where
 
 impl<T> Mul<&T> for Vec6<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Vec6<T>;
 
@@ -71,7 +71,7 @@ where
 
 impl<T> Mul<&T> for &Vec6<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Vec6<T>;
 
@@ -125,6 +125,8 @@ where
 {
     type Algebra = se3<T>;
 
+    const DIM: usize = 6;
+
     fn hat(&self) -> Self::Algebra {
         Self::Algebra { val: self.val }
     }
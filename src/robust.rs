@@ -0,0 +1,59 @@
+//! Robust kernels for iteratively reweighted least squares
+
+use crate::Real;
+
+/// robust kernel `ρ` applied to the norm `r` of a residual
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kernel<T> {
+    /// plain least squares, `ρ(r) = r² / 2`
+    L2,
+    /// Huber kernel with threshold `k`, quadratic below `k` and linear above
+    Huber(T),
+    /// Cauchy kernel with scale `c`, `ρ(r) = c² / 2 ln(1 + (r / c)²)`
+    Cauchy(T),
+}
+
+impl<T: Real> Kernel<T> {
+    /// cost `ρ(r)`
+    pub fn cost(&self, r: T) -> T {
+        let half = T::one() / (T::one() + T::one());
+        let r = r.abs();
+        match *self {
+            Kernel::L2 => half * r * r,
+            Kernel::Huber(k) if r <= k => half * r * r,
+            Kernel::Huber(k) => k * (r - half * k),
+            Kernel::Cauchy(c) => half * c * c * (r / c * (r / c)).ln_1p(),
+        }
+    }
+
+    /// weight `ρ'(r) / r` of the residual in the reweighted least squares
+    pub fn weight(&self, r: T) -> T {
+        let r = r.abs();
+        match *self {
+            Kernel::L2 => T::one(),
+            Kernel::Huber(k) if r <= k => T::one(),
+            Kernel::Huber(k) => k / r,
+            Kernel::Cauchy(c) => T::one() / (T::one() + r / c * (r / c)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_weight_is_derivative() {
+        let h = 1e-6;
+        for kernel in [Kernel::L2, Kernel::Huber(0.5), Kernel::Cauchy(0.5)] {
+            for r in [0.1, 0.4, 0.6, 2., 10.] {
+                let d = (kernel.cost(r + h) - kernel.cost(r - h)) / (2. * h);
+                assert_relative_eq!(kernel.weight(r), d / r, epsilon = 1e-6);
+            }
+        }
+        assert_eq!(Kernel::Huber(1.).weight(4.), 0.25);
+        assert_eq!(Kernel::Cauchy(1.).weight(1.), 0.5);
+    }
+}
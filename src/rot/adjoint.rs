@@ -4,6 +4,7 @@ use nalgebra::Matrix3;
 
 use crate::Adjoint;
 use crate::Real;
+use crate::SimdReal;
 use crate::Vector;
 
 use super::so3;
@@ -28,7 +29,7 @@ where
 
 impl<T> AdjSO3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     /// Create a new AdjSO3 from a slice without checking the contents
     ///
@@ -43,7 +44,7 @@ where
 
 impl<T> Adjoint for AdjSO3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Algebra = so3<T>;
 
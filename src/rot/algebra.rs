@@ -2,8 +2,8 @@ use core::{fmt::Display, ops::Mul};
 
 use super::SO3;
 use crate::{
-    utils::{approx_zero, axis_angle, hat, length},
-    Algebra, Real, Vec3,
+    utils::{hat, length_squared, so3_coefs},
+    Algebra, Real, SimdReal, Vec3,
 };
 use nalgebra::{Matrix3, Vector3};
 
@@ -32,7 +32,7 @@ where
 
 impl<T> so3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     /// Create a new so3 group element
     /// ```ignore
@@ -51,7 +51,7 @@ where
 
 impl<T> Mul<T> for so3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Self;
 
@@ -64,25 +64,19 @@ where
 
 impl<T> Algebra for so3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Group = SO3<T>;
 
     type Vector = Vec3<T>;
 
+    const DIM: usize = 3;
+
     fn exp(&self) -> Self::Group {
-        if approx_zero(length(&self.val)) {
-            SO3 {
-                val: Matrix3::identity(),
-            }
-        } else {
-            let (w, angle) = axis_angle(&self.val);
-            let w_so3 = hat(&w);
-            SO3 {
-                val: Matrix3::identity()
-                    + w_so3 * angle.sin()
-                    + w_so3 * w_so3 * (T::one() - angle.cos()),
-            }
+        let (a, b, _) = so3_coefs(length_squared(&self.val));
+        let w_so3 = hat(&self.val);
+        SO3 {
+            val: Matrix3::identity() + w_so3 * a + w_so3 * w_so3 * b,
         }
     }
 
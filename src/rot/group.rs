@@ -2,7 +2,12 @@ use core::{fmt::Display, ops::Mul};
 
 use nalgebra::{Matrix3, Vector3};
 
-use crate::{point::Point, utils::approx_zero, Group, Real};
+use crate::{
+    point::Point,
+    simd::select_with,
+    utils::{length_squared, quat_to_rot, rot_to_quat, small_angle},
+    Group, Real, SimdReal,
+};
 
 use super::{so3, AdjSO3};
 
@@ -29,7 +34,7 @@ where
 
 impl<T> SO3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     /// Create a new SO3 from a slice without checking the contents
     ///
@@ -56,7 +61,12 @@ where
             slice[8],
         ]
     }
+}
 
+impl<T> SO3<T>
+where
+    T: Real,
+{
     /// Create a new SO3 from euler angles
     /// input are radians
     ///
@@ -83,12 +93,39 @@ where
         );
         Self { val }
     }
+
+    /// Create a new SO3 from a quaternion `[x, y, z, w]`
+    ///
+    /// the quaternion is normalized before conversion
+    ///
+    /// ## Example
+    /// ```rust
+    /// use liealg::rot::SO3;
+    /// let s = std::f64::consts::FRAC_1_SQRT_2;
+    /// let rot = SO3::from_quaternion([0., 0., s, s]);
+    /// ```
+    pub fn from_quaternion(q: [T; 4]) -> Self {
+        Self {
+            val: quat_to_rot(&q),
+        }
+    }
+
+    /// unit quaternion `[x, y, z, w]` of the rotation, with `w >= 0`
+    pub fn quaternion(&self) -> [T; 4] {
+        rot_to_quat(&self.val)
+    }
 }
 
 impl<T> Group for SO3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
+    const DOF: usize = 3;
+
+    fn identity() -> Self {
+        Self::identity()
+    }
+
     type Algebra = so3<T>;
 
     fn log(&self) -> Self::Algebra {
@@ -96,29 +133,42 @@ where
         let one: T = T::one();
         let two = one + one;
         let cos = (rot.trace() - one) / two;
-        if cos >= one {
-            so3 {
-                val: Vector3::zeros(),
-            }
-        } else if cos <= -one {
-            let res;
-            if approx_zero(one + rot[(2, 2)]) {
-                res = Vector3::from_column_slice(&[rot[(0, 2)], rot[(1, 2)], one + rot[(2, 2)]])
-                    / (two * (one + rot[(2, 2)])).sqrt();
-            } else if approx_zero(one + rot[(1, 1)]) {
-                res = Vector3::from_column_slice(&[rot[(0, 1)], one + rot[(1, 1)], rot[(2, 1)]])
-                    / (two * (one + rot[(1, 1)])).sqrt();
-            } else {
-                res = Vector3::from_column_slice(&[rot[(0, 0)], rot[(1, 0)], one + rot[(2, 0)]])
-                    / (two * (one + rot[(0, 0)])).sqrt();
-            }
-            so3 { val: res * T::PI() }
-        } else {
-            let theta = cos.acos();
-            let a = (rot - rot.transpose()) * (theta / two / theta.sin());
-            so3 {
-                val: Vector3::new(a[(2, 1)], a[(0, 2)], a[(1, 0)]),
-            }
+        // 2 sin θ * axis
+        let axis = Vector3::new(
+            rot[(2, 1)] - rot[(1, 2)],
+            rot[(0, 2)] - rot[(2, 0)],
+            rot[(1, 0)] - rot[(0, 1)],
+        );
+        let x = one - cos;
+        let taylor = || -> [T; 3] {
+            // θ² and θ / (2 sin θ) from 1 - cos θ, the derivatives stay exact at θ = 0
+            let c = T::splat;
+            let theta2 = x * two + x * x / c(3.) + x * x * x * c(8. / 45.);
+            let k = c(0.5) + theta2 / c(12.) + theta2 * theta2 * c(7. / 720.);
+            (axis * k).into()
+        };
+        let closed_form = || -> [T; 3] {
+            let sin = length_squared(&axis).simd_sqrt() / two;
+            let theta = sin.simd_atan2(cos);
+            let regular = || -> [T; 3] { (axis * (theta / (two * sin))).into() };
+            let near_pi = || -> [T; 3] {
+                // near π the antisymmetric part vanishes, extract the axis from the symmetric part
+                // (R + R^T) / 2 - cos θ * I = (1 - cos θ) * u * u^T
+                let sym = (rot + rot.transpose()) / two - Matrix3::identity() * cos;
+                let d = sym.diagonal();
+                // the column of the largest diagonal entry, picked lane by lane
+                let first = d.x.simd_ge(d.y) & d.x.simd_ge(d.z);
+                let second = d.y.simd_ge(d.z);
+                let pick = |c0: T, c1: T, c2: T| T::select(first, c0, T::select(second, c1, c2));
+                let col = Vector3::from_fn(|r, _| pick(sym[(r, 0)], sym[(r, 1)], sym[(r, 2)]));
+                let u = col / (x * pick(d.x, d.y, d.z)).simd_sqrt();
+                let flip = u.dot(&axis).simd_lt(T::zero());
+                (u.map(|v| T::select(flip, -v, v)) * theta).into()
+            };
+            select_with(cos.simd_ge(T::zero()), regular, near_pi)
+        };
+        so3 {
+            val: select_with(small_angle(x), taylor, closed_form).into(),
         }
     }
 
@@ -148,7 +198,7 @@ where
     }
 }
 
-impl<T: Real> Mul<T> for SO3<T> {
+impl<T: SimdReal> Mul<T> for SO3<T> {
     type Output = SO3<T>;
     fn mul(self, rhs: T) -> Self::Output {
         SO3 {
@@ -157,7 +207,7 @@ impl<T: Real> Mul<T> for SO3<T> {
     }
 }
 
-impl<T: Real> Mul<&T> for SO3<T> {
+impl<T: SimdReal> Mul<&T> for SO3<T> {
     type Output = SO3<T>;
     fn mul(self, rhs: &T) -> Self::Output {
         SO3 {
@@ -166,7 +216,7 @@ impl<T: Real> Mul<&T> for SO3<T> {
     }
 }
 
-impl<T: Real> Mul<T> for &SO3<T> {
+impl<T: SimdReal> Mul<T> for &SO3<T> {
     type Output = SO3<T>;
     fn mul(self, rhs: T) -> Self::Output {
         SO3 {
@@ -175,7 +225,7 @@ impl<T: Real> Mul<T> for &SO3<T> {
     }
 }
 
-impl<T: Real> Mul<&T> for &SO3<T> {
+impl<T: SimdReal> Mul<&T> for &SO3<T> {
     type Output = SO3<T>;
     fn mul(self, rhs: &T) -> Self::Output {
         SO3 {
@@ -184,28 +234,28 @@ impl<T: Real> Mul<&T> for &SO3<T> {
     }
 }
 
-impl<T: Real> Mul<Point<T>> for SO3<T> {
+impl<T: SimdReal> Mul<Point<T>> for SO3<T> {
     type Output = Point<T>;
     fn mul(self, rhs: Point<T>) -> Self::Output {
         self.act(&rhs)
     }
 }
 
-impl<T: Real> Mul<Point<T>> for &SO3<T> {
+impl<T: SimdReal> Mul<Point<T>> for &SO3<T> {
     type Output = Point<T>;
     fn mul(self, rhs: Point<T>) -> Self::Output {
         self.act(&rhs)
     }
 }
 
-impl<T: Real> Mul<&Point<T>> for SO3<T> {
+impl<T: SimdReal> Mul<&Point<T>> for SO3<T> {
     type Output = Point<T>;
     fn mul(self, rhs: &Point<T>) -> Self::Output {
         self.act(rhs)
     }
 }
 
-impl<T: Real> Mul<&Point<T>> for &SO3<T> {
+impl<T: SimdReal> Mul<&Point<T>> for &SO3<T> {
     type Output = Point<T>;
     fn mul(self, rhs: &Point<T>) -> Self::Output {
         self.act(rhs)
@@ -218,7 +268,7 @@ mod test {
 
     use approx::assert_relative_eq;
 
-    use crate::{rot::Vec3, Vector};
+    use crate::{rot::Vec3, utils::test, Algebra, Vector};
 
     use super::*;
     #[test]
@@ -233,6 +283,22 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_quaternion() {
+        let rot = SO3::<f64>::from_euler_angles(0.3, -1.2, 2.9);
+        let q = rot.quaternion();
+        assert!(q[3] >= 0.);
+        assert_relative_eq!(SO3::from_quaternion(q).val, rot.val, epsilon = 1e-12);
+
+        let rot = SO3::<f64>::from_quaternion([0., 0., 2., 0.]);
+        #[rustfmt::skip]
+        assert_relative_eq!(rot.val, &Matrix3::new(
+            -1., 0., 0.,
+            0., -1., 0.,
+            0., 0., 1.
+        ));
+    }
+
     #[test]
     fn test_log() {
         let rot = SO3 {
@@ -243,6 +309,58 @@ mod test {
             val: Vector3::new(0., 0., FRAC_PI_2),
         };
         assert_relative_eq!(v.hat().val, so3.val);
+
+        let rot = SO3 {
+            val: Matrix3::new(-1., 0., 0., 0., 0., 1., 0., 1., 0.),
+        };
+        let w = rot.log().val * core::f64::consts::FRAC_1_SQRT_2 / core::f64::consts::PI;
+        assert_relative_eq!(w.abs(), Vector3::new(0., 1., 1.) * 0.5, epsilon = 1e-12);
+        assert_relative_eq!(rot.log().exp().val, rot.val, epsilon = 1e-12);
+    }
+
+    /// maximum error of exp and log(exp) relative to machine epsilon
+    fn exp_log_errors<T: Real>() -> (f64, f64) {
+        let eps = T::epsilon().to_f64().unwrap();
+        let (mut exp_err, mut log_err) = (0f64, 0f64);
+        for axis in test::axes() {
+            for theta in test::sweep() {
+                let w = (axis * theta).map(|x| T::from(x).unwrap());
+                let (s, c) = (theta / 2.).sin_cos();
+                let q = [axis.x * s, axis.y * s, axis.z * s, c];
+                let reference = quat_to_rot(&q);
+                let rot = so3 { val: w }.exp();
+                let err = (rot.val.map(|x| x.to_f64().unwrap()) - reference)
+                    .abs()
+                    .max();
+                exp_err = exp_err.max(err / eps);
+
+                // f32 rounds π - 1e-9 above π, log then returns the antipodal vector
+                let theta = length_squared(&w).sqrt();
+                if theta < T::PI() {
+                    let w_ = rot.log().val;
+                    let err = (w_ - w).map(|x| x.to_f64().unwrap()).abs().max();
+                    log_err = log_err.max(err / eps / theta.to_f64().unwrap());
+                } else {
+                    let rot_ = rot.log().exp();
+                    let err = (rot_.val - rot.val)
+                        .map(|x| x.to_f64().unwrap())
+                        .abs()
+                        .max();
+                    exp_err = exp_err.max(err / eps);
+                }
+            }
+        }
+        (exp_err, log_err)
+    }
+
+    #[test]
+    fn test_exp_log_sweep() {
+        let (exp_err, log_err) = exp_log_errors::<f64>();
+        assert!(exp_err <= 8., "{exp_err}");
+        assert!(log_err <= 8., "{log_err}");
+        let (exp_err, log_err) = exp_log_errors::<f32>();
+        assert!(exp_err <= 8., "{exp_err}");
+        assert!(log_err <= 8., "{log_err}");
     }
 
     #[test]
@@ -275,6 +393,17 @@ mod test {
         assert_relative_eq!(rot3.val, &Matrix3::new(0., -1., 0., 1., 0., 0., 0., 0., 1.));
     }
 
+    #[test]
+    fn test_plus_minus() {
+        let rot = SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3);
+        let tau = Vec3::new(0.3, -0.2, 0.1);
+        let other = rot.plus(&tau);
+        assert_relative_eq!(other.minus(&rot), tau, epsilon = 1e-12);
+        assert_relative_eq!(rot.mat_mul(&rot.between(&other)), other, epsilon = 1e-12);
+        assert_eq!(<SO3<f64> as Group>::identity(), SO3::identity());
+        assert_eq!(SO3::<f64>::DOF, 3);
+    }
+
     #[test]
     fn test_act() {
         let rot = SO3 {
@@ -0,0 +1,314 @@
+use nalgebra::Matrix3;
+
+use crate::{
+    utils::{hat, length_squared, set_jac, so3_coefs, so3_jac_inv_coef},
+    Algebra, Group, Perturbation, Point, Real, SimdReal, Vector,
+};
+
+use super::{Vec3, SO3};
+
+impl<T> Vec3<T>
+where
+    T: SimdReal,
+{
+    /// left Jacobian of SO3
+    /// ```text
+    /// exp((v + δ)^) ≈ exp((Jl δ)^) * exp(v^)
+    /// ```
+    pub fn ljac(&self) -> Matrix3<T> {
+        let (_, a, b) = so3_coefs(length_squared(&self.val));
+        let w = hat(&self.val);
+        Matrix3::identity() + w * a + w * w * b
+    }
+
+    /// right Jacobian of SO3
+    /// ```text
+    /// exp((v + δ)^) ≈ exp(v^) * exp((Jr δ)^)
+    /// ```
+    pub fn rjac(&self) -> Matrix3<T> {
+        let (_, a, b) = so3_coefs(length_squared(&self.val));
+        let w = hat(&self.val);
+        Matrix3::identity() - w * a + w * w * b
+    }
+
+    /// inverse of the left Jacobian of SO3
+    pub fn ljac_inv(&self) -> Matrix3<T> {
+        let c = so3_jac_inv_coef(length_squared(&self.val));
+        let w = hat(&self.val);
+        let half = T::one() / (T::one() + T::one());
+        Matrix3::identity() - w * half + w * w * c
+    }
+
+    /// inverse of the right Jacobian of SO3
+    pub fn rjac_inv(&self) -> Matrix3<T> {
+        let c = so3_jac_inv_coef(length_squared(&self.val));
+        let w = hat(&self.val);
+        let half = T::one() / (T::one() + T::one());
+        Matrix3::identity() + w * half + w * w * c
+    }
+
+    /// exp map, `jac` is the right Jacobian
+    pub fn exp_jac(&self, jac: Option<&mut Matrix3<T>>) -> SO3<T> {
+        set_jac(jac, || self.rjac());
+        self.hat().exp()
+    }
+}
+
+/// Operations with optional Jacobians.
+///
+/// All Jacobians are defined on the right (local) tangent space,
+/// `J = d(f(X ⊕ δ) ⊖ f(X)) / dδ`.
+impl<T> SO3<T>
+where
+    T: Real,
+{
+    /// inverse, `X^-1`
+    pub fn inverse(&self, j_self: Option<&mut Matrix3<T>>) -> Self {
+        set_jac(j_self, || -self.val);
+        self.inv()
+    }
+
+    /// composition, `X * Y`
+    pub fn compose(
+        &self,
+        other: &Self,
+        j_self: Option<&mut Matrix3<T>>,
+        j_other: Option<&mut Matrix3<T>>,
+    ) -> Self {
+        set_jac(j_self, || other.val.transpose());
+        set_jac(j_other, Matrix3::identity);
+        self.mat_mul(other)
+    }
+
+    /// right plus, `X ⊕ τ = X * exp(τ^)`
+    pub fn rplus(
+        &self,
+        tau: &Vec3<T>,
+        j_self: Option<&mut Matrix3<T>>,
+        j_tau: Option<&mut Matrix3<T>>,
+    ) -> Self {
+        let exp = tau.exp_jac(j_tau);
+        set_jac(j_self, || exp.val.transpose());
+        self.mat_mul(&exp)
+    }
+
+    /// left plus, `τ ⊕ X = exp(τ^) * X`
+    pub fn lplus(
+        &self,
+        tau: &Vec3<T>,
+        j_self: Option<&mut Matrix3<T>>,
+        j_tau: Option<&mut Matrix3<T>>,
+    ) -> Self {
+        set_jac(j_self, Matrix3::identity);
+        set_jac(j_tau, || self.val.transpose() * tau.rjac());
+        tau.hat().exp().mat_mul(self)
+    }
+
+    /// right minus, `Y ⊖ X = log(X^-1 * Y)∨`, where `self` is `Y`
+    pub fn rminus(
+        &self,
+        other: &Self,
+        j_self: Option<&mut Matrix3<T>>,
+        j_other: Option<&mut Matrix3<T>>,
+    ) -> Vec3<T> {
+        let tau = self.minus(other);
+        set_jac(j_self, || tau.rjac_inv());
+        set_jac(j_other, || -tau.ljac_inv());
+        tau
+    }
+
+    /// left minus, `log(Y * X^-1)∨`, where `self` is `Y`
+    pub fn lminus(
+        &self,
+        other: &Self,
+        j_self: Option<&mut Matrix3<T>>,
+        j_other: Option<&mut Matrix3<T>>,
+    ) -> Vec3<T> {
+        let tau = self.mat_mul(&other.inv()).log().vee();
+        let jac = tau.rjac_inv() * other.val;
+        set_jac(j_other, || -jac);
+        set_jac(j_self, || jac);
+        tau
+    }
+
+    /// log map, `jac` is the inverse right Jacobian
+    pub fn log_jac(&self, jac: Option<&mut Matrix3<T>>) -> Vec3<T> {
+        let tau = self.log().vee();
+        set_jac(jac, || tau.rjac_inv());
+        tau
+    }
+
+    /// action on point, `X * p`
+    pub fn act_jac(
+        &self,
+        point: &Point<T>,
+        j_self: Option<&mut Matrix3<T>>,
+        j_point: Option<&mut Matrix3<T>>,
+    ) -> Point<T> {
+        let (res, jac_self, jac_point) = self.act_with_jacobians(point, Perturbation::Right);
+        set_jac(j_self, || jac_self);
+        set_jac(j_point, || jac_point);
+        res
+    }
+
+    /// action on point, returns `X * p` and its Jacobians with respect to `X` and `p`
+    ///
+    /// ## Example
+    /// ```rust
+    /// use liealg::prelude::*;
+    /// use liealg::{Point, SO3};
+    /// let rot = SO3::<f64>::from_euler_angles(0.1, 0.2, 0.3);
+    /// let (p, j_rot, j_p) = rot.act_with_jacobians(&Point::new(1., 2., 3.), Perturbation::Left);
+    /// ```
+    pub fn act_with_jacobians(
+        &self,
+        point: &Point<T>,
+        perturbation: Perturbation,
+    ) -> (Point<T>, Matrix3<T>, Matrix3<T>) {
+        let res = self.act(point);
+        let j_self = match perturbation {
+            Perturbation::Left => -hat(&res.val),
+            Perturbation::Right => -self.val * hat(&point.val),
+        };
+        (res, j_self, self.val)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::Vector3;
+
+    use super::*;
+
+    const EPS: f64 = 1e-6;
+
+    /// numerical Jacobian of `f` at zero perturbation
+    fn num_jac<F, const N: usize>(f: F) -> nalgebra::SMatrix<f64, N, 3>
+    where
+        F: Fn(&Vec3<f64>) -> nalgebra::SVector<f64, N>,
+    {
+        let f0 = f(&Vec3::new(0., 0., 0.));
+        let mut jac = nalgebra::SMatrix::<f64, N, 3>::zeros();
+        for i in 0..3 {
+            let mut d = Vector3::zeros();
+            d[i] = EPS;
+            let df = (f(&Vec3 { val: d }) - f0) / EPS;
+            jac.set_column(i, &df);
+        }
+        jac
+    }
+
+    fn add(a: &Vec3<f64>, b: &Vec3<f64>) -> Vec3<f64> {
+        Vec3 { val: a.val + b.val }
+    }
+
+    fn x() -> SO3<f64> {
+        SO3::from_euler_angles(0.3, -0.5, 1.2)
+    }
+
+    fn y() -> SO3<f64> {
+        SO3::from_euler_angles(-1.1, 0.2, 2.5)
+    }
+
+    #[test]
+    fn test_jacobians() {
+        for v in [
+            Vec3::new(0.3, -0.2, 0.5),
+            Vec3::new(1e-5, 0., -2e-5),
+            Vec3::new(0., 3., 0.1),
+        ] {
+            let exp = v.hat().exp();
+            let num = num_jac(|d| exp.inv().mat_mul(&add(&v, d).hat().exp()).log().val);
+            assert_relative_eq!(v.rjac(), num, epsilon = 1e-5);
+            let num = num_jac(|d| add(&v, d).hat().exp().mat_mul(&exp.inv()).log().val);
+            assert_relative_eq!(v.ljac(), num, epsilon = 1e-5);
+            assert_relative_eq!(v.rjac() * v.rjac_inv(), Matrix3::identity(), epsilon = 1e-9);
+            assert_relative_eq!(v.ljac() * v.ljac_inv(), Matrix3::identity(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_inverse_compose() {
+        let mut j = Matrix3::zeros();
+        let inv = x().inverse(Some(&mut j));
+        let num = num_jac(|d| inv.inv().mat_mul(&x().plus(d).inv()).log().val);
+        assert_relative_eq!(j, num, epsilon = 1e-5);
+
+        let (mut jx, mut jy) = (Matrix3::zeros(), Matrix3::zeros());
+        let xy = x().compose(&y(), Some(&mut jx), Some(&mut jy));
+        let num = num_jac(|d| x().plus(d).mat_mul(&y()).minus(&xy).val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| x().mat_mul(&y().plus(d)).minus(&xy).val);
+        assert_relative_eq!(jy, num, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_plus_minus() {
+        let tau = Vec3::new(0.3, -0.2, 0.5);
+        let (mut jx, mut jt) = (Matrix3::zeros(), Matrix3::zeros());
+        let r = x().rplus(&tau, Some(&mut jx), Some(&mut jt));
+        assert_relative_eq!(r, x().plus(&tau));
+        let num = num_jac(|d| x().plus(d).plus(&tau).minus(&r).val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| x().plus(&add(&tau, d)).minus(&r).val);
+        assert_relative_eq!(jt, num, epsilon = 1e-5);
+
+        let l = x().lplus(&tau, Some(&mut jx), Some(&mut jt));
+        let num = num_jac(|d| tau.hat().exp().mat_mul(&x().plus(d)).minus(&l).val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| add(&tau, d).hat().exp().mat_mul(&x()).minus(&l).val);
+        assert_relative_eq!(jt, num, epsilon = 1e-5);
+
+        let (mut jy, mut jx) = (Matrix3::zeros(), Matrix3::zeros());
+        let tau = y().rminus(&x(), Some(&mut jy), Some(&mut jx));
+        let num = num_jac(|d| y().plus(d).minus(&x()).val - tau.val);
+        assert_relative_eq!(jy, num, epsilon = 1e-5);
+        let num = num_jac(|d| y().minus(&x().plus(d)).val - tau.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+
+        let tau = y().lminus(&x(), Some(&mut jy), Some(&mut jx));
+        let lminus = |a: &SO3<f64>, b: &SO3<f64>| a.mat_mul(&b.inv()).log().val;
+        let num = num_jac(|d| lminus(&y().plus(d), &x()) - tau.val);
+        assert_relative_eq!(jy, num, epsilon = 1e-5);
+        let num = num_jac(|d| lminus(&y(), &x().plus(d)) - tau.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_exp_log_act() {
+        let tau = Vec3::new(0.3, -0.2, 0.5);
+        let mut j = Matrix3::zeros();
+        let r = tau.exp_jac(Some(&mut j));
+        assert_relative_eq!(j, tau.rjac());
+        let tau_ = r.log_jac(Some(&mut j));
+        assert_relative_eq!(tau_, tau, epsilon = 1e-12);
+        let num = num_jac(|d| r.plus(d).log().val - tau.val);
+        assert_relative_eq!(j, num, epsilon = 1e-5);
+
+        let p = Point::new(1., -2., 3.);
+        let (mut jx, mut jp) = (Matrix3::zeros(), Matrix3::zeros());
+        let q = x().act_jac(&p, Some(&mut jx), Some(&mut jp));
+        let num = num_jac(|d| x().plus(d).act(&p).val - q.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| x().act(&Point { val: p.val + d.val }).val - q.val);
+        assert_relative_eq!(jp, num, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_act_with_jacobians() {
+        let p = Point::new(1., -2., 3.);
+        let (q, jx, jp) = x().act_with_jacobians(&p, Perturbation::Right);
+        assert_relative_eq!(q, x().act(&p));
+        let num = num_jac(|d| x().plus(d).act(&p).val - q.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        assert_relative_eq!(jp, x().val);
+
+        let (q, jx, jp) = x().act_with_jacobians(&p, Perturbation::Left);
+        assert_relative_eq!(q, x().act(&p));
+        let num = num_jac(|d| d.hat().exp().mat_mul(&x()).act(&p).val - q.val);
+        assert_relative_eq!(jx, num, epsilon = 1e-5);
+        let num = num_jac(|d| x().act(&Point { val: p.val + d.val }).val - q.val);
+        assert_relative_eq!(jp, num, epsilon = 1e-5);
+    }
+}
@@ -3,9 +3,12 @@
 mod adjoint;
 mod algebra;
 mod group;
+mod jacobian;
+mod uncertain;
 mod vector;
 
 pub use adjoint::AdjSO3;
 pub use algebra::so3;
 pub use group::SO3;
+pub use uncertain::UncertainSO3;
 pub use vector::Vec3;
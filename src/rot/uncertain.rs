@@ -0,0 +1,118 @@
+use nalgebra::Matrix3;
+
+use crate::{
+    utils::{bracket, double_bracket},
+    Group, Perturbation, Propagation, Real,
+};
+
+use super::SO3;
+
+/// concentrated gaussian on SO3
+///
+/// ```ignore
+/// R = exp(ξ^) * mean, ξ ~ N(0, cov)
+/// ```
+/// the covariance is expressed on the left (global frame), as in Barfoot and Furgale
+#[derive(Debug)]
+pub struct UncertainSO3<T> {
+    pub(crate) mean: SO3<T>,
+    pub(crate) cov: Matrix3<T>,
+}
+
+impl<T: Real> UncertainSO3<T> {
+    /// Create a new uncertain rotation from its mean and its left covariance
+    pub fn new(mean: SO3<T>, cov: Matrix3<T>) -> Self {
+        Self { mean, cov }
+    }
+
+    /// Create a new uncertain rotation with the covariance of a perturbation on the given side
+    pub fn with_perturbation(mean: SO3<T>, cov: Matrix3<T>, side: Perturbation) -> Self {
+        let cov = match side {
+            Perturbation::Left => cov,
+            Perturbation::Right => mean.val * cov * mean.val.transpose(),
+        };
+        Self { mean, cov }
+    }
+
+    /// mean rotation
+    pub fn mean(&self) -> &SO3<T> {
+        &self.mean
+    }
+
+    /// covariance of the perturbation on the given side
+    pub fn cov(&self, side: Perturbation) -> Matrix3<T> {
+        match side {
+            Perturbation::Left => self.cov,
+            Perturbation::Right => self.mean.val.transpose() * self.cov * self.mean.val,
+        }
+    }
+
+    /// compose two independent uncertain rotations, `self * other`
+    pub fn compose(&self, other: &Self, order: Propagation) -> Self {
+        let ad = self.mean.val;
+        let s1 = self.cov;
+        let s2 = ad * other.cov * ad.transpose();
+        let mut cov = s1 + s2;
+        if order == Propagation::FourthOrder {
+            let a1 = bracket(&s1);
+            let a2 = bracket(&s2);
+            let twelve = T::from(12).unwrap();
+            let four = T::from(4).unwrap();
+            cov += (a1 * s2 + s2 * a1.transpose() + a2 * s1 + s1 * a2.transpose()) / twelve
+                + double_bracket(&s1, &s2) / four;
+        }
+        Self {
+            mean: self.mean.mat_mul(&other.mean),
+            cov,
+        }
+    }
+
+    /// inverse, the covariance is propagated exactly
+    pub fn inverse(&self) -> Self {
+        let r_inv = self.mean.val.transpose();
+        Self {
+            mean: self.mean.inv(),
+            cov: r_inv * self.cov * self.mean.val,
+        }
+    }
+
+    /// relative rotation `self⁻¹ * other` of two independent uncertain rotations
+    pub fn between(&self, other: &Self, order: Propagation) -> Self {
+        self.inverse().compose(other, order)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_compose_inverse() {
+        let r1 = SO3::from_euler_angles(0.1, 0.2, 0.3);
+        let r2 = SO3::from_euler_angles(-0.5, 0.4, 1.2);
+        let c1 = Matrix3::new(0.01, 0.002, 0., 0.002, 0.02, 0.001, 0., 0.001, 0.03);
+        let c2 = Matrix3::from_diagonal_element(0.05);
+        let u1 = UncertainSO3::new(r1.clone(), c1);
+        let u2 = UncertainSO3::new(r2.clone(), c2);
+
+        let u = u1.compose(&u2, Propagation::SecondOrder);
+        assert_relative_eq!(u.mean, r1.mat_mul(&r2));
+        assert_relative_eq!(u.cov, c1 + c2, epsilon = 1e-12);
+        let u4 = u1.compose(&u2, Propagation::FourthOrder);
+        assert_relative_eq!(u4.cov, u4.cov.transpose(), epsilon = 1e-15);
+        assert!((u4.cov - u.cov).norm() < 1e-3);
+
+        let inv = u1.inverse().inverse();
+        assert_relative_eq!(inv.mean, r1, epsilon = 1e-12);
+        assert_relative_eq!(inv.cov, c1, epsilon = 1e-12);
+        assert_relative_eq!(u1.inverse().cov(Perturbation::Right), c1, epsilon = 1e-12);
+
+        let rel = u1.between(&u1, Propagation::SecondOrder);
+        assert_relative_eq!(rel.mean, SO3::identity(), epsilon = 1e-12);
+
+        let right = UncertainSO3::with_perturbation(r1, c1, Perturbation::Right);
+        assert_relative_eq!(right.cov(Perturbation::Right), c1, epsilon = 1e-12);
+    }
+}
@@ -5,7 +5,7 @@ use core::{
 
 use nalgebra::Vector3;
 
-use crate::{Real, Vector};
+use crate::{Real, SimdReal, Vector};
 
 use super::so3;
 
@@ -29,7 +29,7 @@ where
 
 impl<T> Mul<T> for Vec3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Vec3<T>;
 
@@ -42,7 +42,7 @@ where
 
 impl<T> Mul<T> for &Vec3<T>
 where
-    T: Real,
+    T: SimdReal,
 {
     type Output = Vec3<T>;
 
@@ -78,6 +78,8 @@ where
 {
     type Algebra = so3<T>;
 
+    const DIM: usize = 3;
+
     fn hat(&self) -> Self::Algebra {
         so3 { val: self.val }
     }
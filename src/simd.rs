@@ -0,0 +1,242 @@
+use core::ops::{BitAnd, Neg, Not};
+
+use nalgebra::{
+    ClosedAddAssign, ClosedDivAssign, ClosedMulAssign, ClosedSubAssign, Scalar, SimdBool,
+    SimdComplexField, SimdPartialOrd, SimdRealField, SimdValue,
+};
+use num_traits::{One, Zero};
+
+/// # lane-wise real number trait
+/// the scalar operations of the maps of [SO3](crate::SO3), [SE3](crate::SE3), their algebras and [Point](crate::Point)
+///
+/// a value may hold several lanes that are evaluated at once, such as simba's `WideF32x8`,
+/// comparisons return a lane mask and the maps select between branches lane by lane,
+/// so one call to `exp`, `log` or `act` processes one pose per lane.
+///
+/// implemented for every [nalgebra::SimdRealField] (`f32`, `f64` and the simba SIMD types) and for [Dual](crate::Dual),
+/// other scalar types implement it with `Mask = bool`.
+///
+/// ## Example
+/// ```rust
+/// use liealg::prelude::*;
+/// use liealg::Vec3;
+/// use simba::simd::WideF32x8;
+/// // eight rotations about z, one per lane
+/// let angle = WideF32x8::from([0., 1e-6, 0.5, 1., 1.5, 2., 2.5, 3.]);
+/// let zero = WideF32x8::from([0.; 8]);
+/// let rot = Vec3::new(zero, zero, angle).hat().exp();
+/// let [_, _, z] = rot.log().vee().as_array();
+/// assert!(<[f32; 8]>::from(z).iter().zip(<[f32; 8]>::from(angle)).all(|(a, b)| (a - b).abs() < 1e-6));
+/// ```
+pub trait SimdReal:
+    Scalar
+    + Copy
+    + Zero
+    + One
+    + ClosedAddAssign
+    + ClosedSubAssign
+    + ClosedMulAssign
+    + ClosedDivAssign
+    + Neg<Output = Self>
+{
+    /// result of a lane-wise comparison, `bool` for scalars
+    type Mask: Copy + BitAnd<Output = Self::Mask> + Not<Output = Self::Mask>;
+
+    /// `x` in every lane
+    fn splat(x: f64) -> Self;
+
+    /// machine epsilon in every lane
+    fn simd_epsilon() -> Self;
+
+    /// square root
+    fn simd_sqrt(self) -> Self;
+
+    /// sine and cosine
+    fn simd_sin_cos(self) -> (Self, Self);
+
+    /// four quadrant arctangent of `self` (y) and `x`
+    fn simd_atan2(self, x: Self) -> Self;
+
+    /// `self < other`
+    fn simd_lt(self, other: Self) -> Self::Mask;
+
+    /// `self >= other`
+    fn simd_ge(self, other: Self) -> Self::Mask;
+
+    /// `if_true` in the lanes where `mask` is set, `if_false` in the others
+    fn select(mask: Self::Mask, if_true: Self, if_false: Self) -> Self;
+
+    /// whether `mask` is set in every lane
+    fn all(mask: Self::Mask) -> bool;
+
+    /// whether `mask` is set in no lane
+    fn none(mask: Self::Mask) -> bool;
+}
+
+impl<T> SimdReal for T
+where
+    T: SimdRealField + Copy,
+{
+    type Mask = T::SimdBool;
+
+    #[inline]
+    fn splat(x: f64) -> Self {
+        nalgebra::convert(x)
+    }
+
+    #[inline]
+    fn simd_epsilon() -> Self {
+        T::simd_default_epsilon()
+    }
+
+    #[inline]
+    fn simd_sqrt(self) -> Self {
+        SimdComplexField::simd_sqrt(self)
+    }
+
+    #[inline]
+    fn simd_sin_cos(self) -> (Self, Self) {
+        SimdComplexField::simd_sin_cos(self)
+    }
+
+    #[inline]
+    fn simd_atan2(self, x: Self) -> Self {
+        SimdRealField::simd_atan2(self, x)
+    }
+
+    #[inline]
+    fn simd_lt(self, other: Self) -> Self::Mask {
+        SimdPartialOrd::simd_lt(self, other)
+    }
+
+    #[inline]
+    fn simd_ge(self, other: Self) -> Self::Mask {
+        SimdPartialOrd::simd_ge(self, other)
+    }
+
+    #[inline]
+    fn select(mask: Self::Mask, if_true: Self, if_false: Self) -> Self {
+        SimdValue::select(if_true, mask, if_false)
+    }
+
+    #[inline]
+    fn all(mask: Self::Mask) -> bool {
+        SimdBool::all(mask)
+    }
+
+    #[inline]
+    fn none(mask: Self::Mask) -> bool {
+        SimdBool::none(mask)
+    }
+}
+
+/// `if_true()` in the lanes where `mask` is set, `if_false()` in the others,
+/// a branch is only evaluated if some lane takes it, so scalars evaluate exactly one
+#[inline]
+pub(crate) fn select_with<T: SimdReal, const N: usize>(
+    mask: T::Mask,
+    if_true: impl FnOnce() -> [T; N],
+    if_false: impl FnOnce() -> [T; N],
+) -> [T; N] {
+    if T::all(mask) {
+        if_true()
+    } else if T::none(mask) {
+        if_false()
+    } else {
+        let (a, b) = (if_true(), if_false());
+        core::array::from_fn(|i| T::select(mask, a[i], b[i]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::{Matrix3, Vector3};
+    use simba::simd::WideF32x8;
+
+    use crate::{Algebra, Group, Point, Vec3, Vec6, Vector, SE3, SO3};
+
+    /// one rotation vector per lane, the lanes take every branch of exp and log:
+    /// zero, small angles, the series, the closed forms and angles past π/2 with each column picked near π
+    const ROTATIONS: [[f32; 3]; 8] = [
+        [0., 0., 0.],
+        [1e-5, -2e-5, 0.],
+        [0.3, -0.2, 0.1],
+        [1., 2., -0.5],
+        [0., 0., 3.1],
+        [-1.7, 1.7, 1.7],
+        [0.5, 0., 0.],
+        [2., 0.1, -1.],
+    ];
+
+    const TRANSLATION: [f32; 3] = [1., -0.5, 2.];
+
+    fn lanes(f: impl Fn(usize) -> f32) -> WideF32x8 {
+        WideF32x8::from(core::array::from_fn::<f32, 8, _>(f))
+    }
+
+    fn lane(x: WideF32x8, i: usize) -> f32 {
+        <[f32; 8]>::from(x)[i]
+    }
+
+    fn point() -> Point<WideF32x8> {
+        Point::new(
+            lanes(|i| i as f32),
+            lanes(|i| 1. - i as f32),
+            lanes(|_| 0.5),
+        )
+    }
+
+    #[test]
+    fn test_so3_lanes() {
+        let w = Vec3::new(
+            lanes(|i| ROTATIONS[i][0]),
+            lanes(|i| ROTATIONS[i][1]),
+            lanes(|i| ROTATIONS[i][2]),
+        );
+        let rot = w.hat().exp();
+        let scalar = ROTATIONS.map(|[x, y, z]| Vec3::new(x, y, z).hat().exp());
+        // log and act of the scalar rotations stacked in lanes
+        let stacked = SO3 {
+            val: Matrix3::from_fn(|r, c| lanes(|i| scalar[i].val[(r, c)])),
+        };
+        let log = stacked.log().vee();
+        let p = point();
+        let q = stacked.act(&p);
+        for (i, s) in scalar.iter().enumerate() {
+            assert_relative_eq!(rot.val.map(|x| lane(x, i)), s.val, epsilon = 1e-6);
+            let log_ = s.log().vee();
+            assert_relative_eq!(log.val.map(|x| lane(x, i)), log_.val, epsilon = 1e-6);
+            let p_ = Point::new(lane(p.x(), i), lane(p.y(), i), lane(p.z(), i));
+            let q_ = s.act(&p_);
+            assert_relative_eq!(q.val.map(|x| lane(x, i)), q_.val, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_se3_lanes() {
+        let xi = Vec6::new(
+            [0, 1, 2].map(|k| lanes(|i| ROTATIONS[i][k])),
+            TRANSLATION.map(|t| lanes(|_| t)),
+        );
+        let t = xi.hat().exp();
+        let scalar = ROTATIONS.map(|r| Vec6::new(r, TRANSLATION).hat().exp());
+        let stacked = SE3 {
+            rot: SO3 {
+                val: Matrix3::from_fn(|r, c| lanes(|i| scalar[i].rot.val[(r, c)])),
+            },
+            trans: Vector3::from_fn(|r, _| lanes(|i| scalar[i].trans[r])),
+        };
+        let log = stacked.log().vee();
+        let p = point();
+        let q = stacked.act(&p);
+        for (i, s) in scalar.iter().enumerate() {
+            assert_relative_eq!(t.matrix().map(|x| lane(x, i)), s.matrix(), epsilon = 1e-6);
+            let log_ = s.log().vee();
+            assert_relative_eq!(log.val.map(|x| lane(x, i)), log_.val, epsilon = 1e-5);
+            let p_ = Point::new(lane(p.x(), i), lane(p.y(), i), lane(p.z(), i));
+            let q_ = s.act(&p_);
+            assert_relative_eq!(q.val.map(|x| lane(x, i)), q_.val, epsilon = 1e-6);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Transforms and points tagged with their frames, checked at compile time
+//!
+//! `Tagged<G, A, B>` is the transform `T_A_B` mapping coordinates in frame `B` to frame `A`,
+//! the frames are marker types which are never instantiated.
+//! composition only compiles if the inner frames match, and the inverse swaps the frames.
+//!
+//! ```rust
+//! use liealg::tagged::{Pose, TaggedPoint};
+//! use liealg::{Point, SE3, SO3};
+//!
+//! enum World {}
+//! enum Robot {}
+//! enum Camera {}
+//!
+//! let world_robot: Pose<f64, World, Robot> =
+//!     Pose::new(SE3::new(&SO3::from_euler_angles(0., 0., 1.), [1., 2., 0.]));
+//! let robot_camera: Pose<f64, Robot, Camera> =
+//!     Pose::new(SE3::new(&SO3::identity(), [0.2, 0., 0.5]));
+//! let world_camera = world_robot.mat_mul(&robot_camera);
+//! let p: TaggedPoint<f64, Camera> = TaggedPoint::new(Point::new(0., 0., 1.));
+//! let p_world: TaggedPoint<f64, World> = world_camera.act(&p);
+//! let camera_world: Pose<f64, Camera, World> = world_camera.inv();
+//! ```
+//!
+//! composing transforms whose inner frames differ does not compile
+//! ```compile_fail
+//! # use liealg::tagged::Pose;
+//! # use liealg::SE3;
+//! # enum World {}
+//! # enum Robot {}
+//! # enum Camera {}
+//! let world_robot: Pose<f64, World, Robot> = Pose::new(SE3::identity());
+//! let world_camera: Pose<f64, World, Camera> = Pose::new(SE3::identity());
+//! let wrong = world_robot.mat_mul(&world_camera);
+//! ```
+//!
+//! and neither does applying a transform to a point in another frame
+//! ```compile_fail
+//! # use liealg::tagged::{Pose, TaggedPoint};
+//! # use liealg::{Point, SE3};
+//! # enum World {}
+//! # enum Camera {}
+//! let world_camera: Pose<f64, World, Camera> = Pose::new(SE3::identity());
+//! let p: TaggedPoint<f64, World> = TaggedPoint::new(Point::new(0., 0., 1.));
+//! let wrong = world_camera.act(&p);
+//! ```
+
+use core::{fmt::Debug, marker::PhantomData};
+
+use crate::{Group, Point, SE3, SO3};
+
+/// transform `T_A_B` from frame `B` to frame `A`
+pub struct Tagged<G, A, B> {
+    inner: G,
+    // fn() keeps the markers out of the auto traits and drop check
+    frames: PhantomData<fn() -> (A, B)>,
+}
+
+/// rigid transform `T_A_B` from frame `B` to frame `A`
+pub type Pose<T, A, B> = Tagged<SE3<T>, A, B>;
+
+/// rotation `R_A_B` from frame `B` to frame `A`
+pub type Rotation<T, A, B> = Tagged<SO3<T>, A, B>;
+
+impl<G, A, B> Tagged<G, A, B> {
+    /// tag a transform from frame `B` to frame `A`
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            frames: PhantomData,
+        }
+    }
+
+    /// untagged transform
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// untagged transform
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+}
+
+impl<G: Group, A> Tagged<G, A, A> {
+    /// identity transform of a frame
+    pub fn identity() -> Self {
+        Self::new(G::identity())
+    }
+}
+
+impl<G: Group, A, B> Tagged<G, A, B> {
+    /// composition `T_A_C = T_A_B * T_B_C`
+    pub fn mat_mul<C>(&self, other: &Tagged<G, B, C>) -> Tagged<G, A, C> {
+        Tagged::new(self.inner.mat_mul(&other.inner))
+    }
+
+    /// inverse `T_B_A`
+    pub fn inv(&self) -> Tagged<G, B, A> {
+        Tagged::new(self.inner.inv())
+    }
+
+    /// relative transform `T_B_C = T_A_B⁻¹ * T_A_C`
+    pub fn between<C>(&self, other: &Tagged<G, A, C>) -> Tagged<G, B, C> {
+        Tagged::new(self.inner.between(&other.inner))
+    }
+}
+
+impl<G, T, A, B> Tagged<G, A, B>
+where
+    G: Group<Point = Point<T>>,
+{
+    /// map a point in frame `B` to frame `A`
+    pub fn act(&self, point: &TaggedPoint<T, B>) -> TaggedPoint<T, A> {
+        TaggedPoint::new(self.inner.act(&point.inner))
+    }
+}
+
+impl<G: Clone, A, B> Clone for Tagged<G, A, B> {
+    fn clone(&self) -> Self {
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<G: PartialEq, A, B> PartialEq for Tagged<G, A, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<G: Debug, A, B> Debug for Tagged<G, A, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Tagged")
+            .field("target", &core::any::type_name::<A>())
+            .field("source", &core::any::type_name::<B>())
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// point in frame `F`
+pub struct TaggedPoint<T, F> {
+    inner: Point<T>,
+    frame: PhantomData<fn() -> F>,
+}
+
+impl<T, F> TaggedPoint<T, F> {
+    /// tag a point in frame `F`
+    pub fn new(inner: Point<T>) -> Self {
+        Self {
+            inner,
+            frame: PhantomData,
+        }
+    }
+
+    /// untagged point
+    pub fn inner(&self) -> &Point<T> {
+        &self.inner
+    }
+
+    /// untagged point
+    pub fn into_inner(self) -> Point<T> {
+        self.inner
+    }
+}
+
+impl<T: Clone, F> Clone for TaggedPoint<T, F> {
+    fn clone(&self) -> Self {
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<T: PartialEq, F> PartialEq for TaggedPoint<T, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: Debug, F> Debug for TaggedPoint<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaggedPoint")
+            .field("frame", &core::any::type_name::<F>())
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    enum World {}
+    enum Robot {}
+    enum Camera {}
+
+    fn world_robot() -> Pose<f64, World, Robot> {
+        Pose::new(SE3::new(
+            &SO3::from_euler_angles(0.1, 0.2, 1.),
+            [1., 2., 0.],
+        ))
+    }
+
+    fn robot_camera() -> Pose<f64, Robot, Camera> {
+        Pose::new(SE3::new(
+            &SO3::from_euler_angles(-1.5, 0., -1.5),
+            [0.2, 0., 0.5],
+        ))
+    }
+
+    #[test]
+    fn test_pose() {
+        let world_camera = world_robot().mat_mul(&robot_camera());
+        assert_eq!(
+            world_camera.inner(),
+            &world_robot().inner().mat_mul(robot_camera().inner())
+        );
+        let camera_world: Pose<f64, Camera, World> = world_camera.inv();
+        let identity: Pose<f64, World, World> = world_camera.mat_mul(&camera_world);
+        assert_relative_eq!(
+            identity.inner(),
+            Pose::<f64, World, World>::identity().inner(),
+            epsilon = 1e-12
+        );
+        let robot_camera = world_robot().between(&world_camera);
+        assert_relative_eq!(
+            robot_camera.inner(),
+            self::robot_camera().inner(),
+            epsilon = 1e-12
+        );
+
+        let p = TaggedPoint::<_, Camera>::new(Point::new(0.1, -0.2, 2.));
+        let p_world: TaggedPoint<f64, World> = world_camera.act(&p);
+        let p_back = camera_world.act(&p_world);
+        assert_relative_eq!(p_back.inner(), p.inner(), epsilon = 1e-12);
+        assert_relative_eq!(
+            p_world.into_inner(),
+            world_robot()
+                .into_inner()
+                .act(&self::robot_camera().into_inner().act(p.inner())),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_rotation() {
+        let r: Rotation<f64, World, Camera> = Rotation::new(SO3::from_euler_angles(0., 0., 1.));
+        let p = r.act(&TaggedPoint::new(Point::new(1., 0., 0.)));
+        assert_relative_eq!(
+            p.inner(),
+            &Point::new(1f64.cos(), 1f64.sin(), 0.),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            r.inv().mat_mul(&r).inner(),
+            Rotation::<f64, Camera, Camera>::identity().inner(),
+            epsilon = 1e-12
+        );
+        assert!(format!("{:?}", r).contains("Camera"));
+    }
+}
@@ -0,0 +1,376 @@
+//! Timestamped poses, and the [Trajectory] container which requires the `alloc` feature
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::Vec6;
+use crate::{Group, Real, SE3};
+
+/// pose with its timestamp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stamped<T> {
+    /// timestamp, in seconds for TUM files
+    pub time: T,
+    /// pose
+    pub pose: SE3<T>,
+}
+
+impl<T: Real> Stamped<T> {
+    /// pose at the time along the geodesic `Tₐ exp(s log(Tₐ⁻¹ T_b))` to a later pose
+    pub(crate) fn interpolate(&self, other: &Self, time: T) -> SE3<T> {
+        let s = (time - self.time) / (other.time - self.time);
+        self.pose.plus(&(other.pose.minus(&self.pose) * s))
+    }
+}
+
+/// poses sorted by time
+///
+/// poses with equal timestamps keep their insertion order
+///
+/// ```rust
+/// use liealg::prelude::*;
+/// use liealg::trajectory::Trajectory;
+/// use liealg::{Vec6, SE3};
+///
+/// let twist = Vec6::new([0., 0., 0.5], [1., 0., 0.]);
+/// let trajectory: Trajectory<f64> = (0..10)
+///     .map(|i| {
+///         let t = i as f64 * 0.1;
+///         (t, (twist.clone() * t).hat().exp())
+///     })
+///     .collect::<Vec<_>>()
+///     .into();
+/// let pose = trajectory.interpolate(0.25).unwrap();
+/// let resampled = trajectory.resample(20.);
+/// assert_eq!(resampled.len(), 19);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory<T> {
+    poses: Vec<Stamped<T>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> Default for Trajectory<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> Trajectory<T> {
+    /// empty trajectory
+    pub fn new() -> Self {
+        Self { poses: Vec::new() }
+    }
+
+    /// trajectory of the poses, sorted by time
+    pub fn from_stamped(mut poses: Vec<Stamped<T>>) -> Self {
+        poses.sort_by(|a, b| {
+            a.time
+                .partial_cmp(&b.time)
+                .unwrap_or(core::cmp::Ordering::Equal)
+        });
+        Self { poses }
+    }
+
+    /// insert a pose after the poses which are not later
+    pub fn push(&mut self, time: T, pose: SE3<T>) {
+        let k = self.poses.partition_point(|s| s.time <= time);
+        self.poses.insert(k, Stamped { time, pose });
+    }
+
+    /// number of poses
+    pub fn len(&self) -> usize {
+        self.poses.len()
+    }
+
+    /// whether there is no pose
+    pub fn is_empty(&self) -> bool {
+        self.poses.is_empty()
+    }
+
+    /// poses sorted by time
+    pub fn as_slice(&self) -> &[Stamped<T>] {
+        &self.poses
+    }
+
+    /// iterator over the poses sorted by time
+    pub fn iter(&self) -> core::slice::Iter<'_, Stamped<T>> {
+        self.poses.iter()
+    }
+
+    /// times of the first and the last pose
+    pub fn time_range(&self) -> Option<(T, T)> {
+        Some((self.poses.first()?.time, self.poses.last()?.time))
+    }
+
+    /// pose at the time, interpolated along the geodesic `Tₐ exp(s log(Tₐ⁻¹ T_b))`
+    /// between the neighbouring poses, `None` outside of the time range
+    pub fn interpolate(&self, time: T) -> Option<SE3<T>> {
+        // number of poses which are not later, 0 for a NaN time
+        let k = self.poses.partition_point(|s| s.time <= time);
+        if k == 0 {
+            return None;
+        }
+        let a = &self.poses[k - 1];
+        if a.time == time {
+            // the first of equal timestamps
+            let first = self.poses.partition_point(|s| s.time < time);
+            return Some(self.poses[first].pose.clone());
+        }
+        Some(a.interpolate(self.poses.get(k)?, time))
+    }
+
+    /// poses interpolated at the given rate in Hz from the first pose, up to the last pose
+    ///
+    /// the trajectory is empty if the rate is not positive and finite,
+    /// or if the number of poses does not fit in `usize`
+    pub fn resample(&self, rate: T) -> Self {
+        let mut poses = Vec::new();
+        // also rejects NaN
+        if !(rate > T::zero() && rate <= T::max_value()) {
+            return Self { poses };
+        }
+        if let Some((start, end)) = self.time_range() {
+            // the count bounds the loop even when `k / rate` is below the resolution of the times
+            let Some(n) = ((end - start) * rate).floor().to_usize() else {
+                return Self { poses };
+            };
+            for k in 0..=n {
+                let time = start + T::from(k).unwrap() / rate;
+                match self.interpolate(time) {
+                    Some(pose) if time <= end => poses.push(Stamped { time, pose }),
+                    _ => break,
+                }
+            }
+        }
+        Self { poses }
+    }
+
+    /// remove the latest pose
+    pub fn pop(&mut self) -> Option<Stamped<T>> {
+        self.poses.pop()
+    }
+
+    /// remove the poses which are not needed to interpolate at the time or later,
+    /// the latest pose which is not later than the time is kept
+    pub fn truncate_before(&mut self, time: T) {
+        let k = self.poses.partition_point(|s| s.time <= time);
+        if k > 1 {
+            self.poses.drain(..k - 1);
+        }
+    }
+
+    /// add the offset to every timestamp
+    pub fn shift(&mut self, offset: T) {
+        for s in &mut self.poses {
+            s.time += offset;
+        }
+    }
+
+    /// relative motions `Tᵢ⁻¹ Tᵢ₊₁` between consecutive poses
+    pub fn relative_motions(&self) -> impl Iterator<Item = SE3<T>> + '_ {
+        self.poses.windows(2).map(|w| w[0].pose.between(&w[1].pose))
+    }
+
+    /// relative motion `T(t₀)⁻¹ T(t₁)` between two interpolated poses
+    pub fn relative(&self, t0: T, t1: T) -> Option<SE3<T>> {
+        Some(self.interpolate(t0)?.between(&self.interpolate(t1)?))
+    }
+
+    /// body velocities `log(Tᵢ⁻¹ Tᵢ₊₁) / dt`, stamped at the middle of each interval
+    ///
+    /// intervals without duration are skipped
+    pub fn body_velocities(&self) -> Vec<(T, Vec6<T>)> {
+        self.velocities(|a, b| b.minus(a))
+    }
+
+    /// space velocities `log(Tᵢ₊₁ Tᵢ⁻¹) / dt`, stamped at the middle of each interval
+    ///
+    /// intervals without duration are skipped
+    pub fn space_velocities(&self) -> Vec<(T, Vec6<T>)> {
+        // log(Tᵢ₊₁ Tᵢ⁻¹) = Ad(Tᵢ) log(Tᵢ⁻¹ Tᵢ₊₁)
+        self.velocities(|a, b| Vec6 {
+            val: a.adjoint().val * b.minus(a).val,
+        })
+    }
+
+    fn velocities(&self, log: impl Fn(&SE3<T>, &SE3<T>) -> Vec6<T>) -> Vec<(T, Vec6<T>)> {
+        let two = T::one() + T::one();
+        self.poses
+            .windows(2)
+            .filter(|w| w[1].time > w[0].time)
+            .map(|w| {
+                let dt = w[1].time - w[0].time;
+                let v = log(&w[0].pose, &w[1].pose) * (T::one() / dt);
+                ((w[0].time + w[1].time) / two, v)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> From<Vec<(T, SE3<T>)>> for Trajectory<T> {
+    fn from(poses: Vec<(T, SE3<T>)>) -> Self {
+        poses.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> FromIterator<(T, SE3<T>)> for Trajectory<T> {
+    fn from_iter<I: IntoIterator<Item = (T, SE3<T>)>>(iter: I) -> Self {
+        iter.into_iter()
+            .map(|(time, pose)| Stamped { time, pose })
+            .collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Real> FromIterator<Stamped<T>> for Trajectory<T> {
+    fn from_iter<I: IntoIterator<Item = Stamped<T>>>(iter: I) -> Self {
+        Self::from_stamped(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T> IntoIterator for &'a Trajectory<T> {
+    type Item = &'a Stamped<T>;
+    type IntoIter = core::slice::Iter<'a, Stamped<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.poses.iter()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{Algebra, Vector, SO3};
+
+    fn start() -> SE3<f64> {
+        SE3::new(&SO3::from_euler_angles(0.3, -0.2, 1.), [1., 2., 3.])
+    }
+
+    fn twist() -> Vec6<f64> {
+        Vec6::new([0.1, -0.2, 0.5], [1., 0.3, -0.2])
+    }
+
+    /// constant body velocity from `start`, `T(t) = T₀ exp(t ξ^)`, at uneven times
+    fn trajectory() -> Trajectory<f64> {
+        [0.3, 0., 0.1, 0.25, 0.5, 0.45]
+            .into_iter()
+            .map(|t| (t, start().mat_mul(&(twist() * t).hat().exp())))
+            .collect()
+    }
+
+    #[test]
+    fn test_sorted() {
+        let mut trajectory = trajectory();
+        let times: Vec<_> = trajectory.iter().map(|s| s.time).collect();
+        assert_eq!(times, [0., 0.1, 0.25, 0.3, 0.45, 0.5]);
+        assert_eq!(trajectory.time_range(), Some((0., 0.5)));
+        trajectory.push(0.2, SE3::identity());
+        trajectory.push(0.2, start());
+        assert_eq!(trajectory.as_slice()[2].time, 0.2);
+        assert_eq!(trajectory.as_slice()[3].pose, start());
+        // the first of equal timestamps
+        assert_eq!(trajectory.interpolate(0.2), Some(SE3::identity()));
+        trajectory.shift(1.);
+        assert_eq!(trajectory.time_range(), Some((1., 1.5)));
+        assert_eq!(trajectory.pop().map(|s| s.time), Some(1.5));
+        // 1.2 is kept to interpolate at 1.22
+        trajectory.truncate_before(1.22);
+        assert_eq!(trajectory.time_range(), Some((1.2, 1.45)));
+        assert!(trajectory.interpolate(1.22).is_some());
+        trajectory.truncate_before(0.);
+        assert_eq!(trajectory.len(), 4);
+        assert_eq!(Trajectory::<f64>::new().time_range(), None);
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let trajectory = trajectory();
+        for t in [0., 0.05, 0.2, 0.3, 0.37, 0.5] {
+            let expected = start().mat_mul(&(twist() * t).hat().exp());
+            assert_relative_eq!(
+                trajectory.interpolate(t).unwrap(),
+                expected,
+                epsilon = 1e-12
+            );
+        }
+        assert_eq!(trajectory.interpolate(-0.01), None);
+        assert_eq!(trajectory.interpolate(0.51), None);
+        assert_eq!(trajectory.interpolate(f64::NAN), None);
+        assert_eq!(Trajectory::new().interpolate(0.), None::<SE3<f64>>);
+
+        let relative = trajectory.relative(0.1, 0.4).unwrap();
+        assert_relative_eq!(relative, (twist() * 0.3).hat().exp(), epsilon = 1e-12);
+        let motions: Vec<_> = trajectory.relative_motions().collect();
+        assert_eq!(motions.len(), 5);
+        assert_relative_eq!(motions[1], (twist() * 0.15).hat().exp(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_resample() {
+        let trajectory = trajectory();
+        let resampled = trajectory.resample(10.);
+        assert_eq!(resampled.len(), 6);
+        for (k, s) in resampled.iter().enumerate() {
+            assert_relative_eq!(s.time, k as f64 * 0.1);
+            let expected = start().mat_mul(&(twist() * s.time).hat().exp());
+            assert_relative_eq!(s.pose, expected, epsilon = 1e-12);
+        }
+        assert_eq!(trajectory.resample(3.).len(), 2);
+        assert!(Trajectory::<f64>::new().resample(10.).is_empty());
+    }
+
+    #[test]
+    fn test_resample_rate() {
+        let trajectory = trajectory();
+        for rate in [
+            f64::INFINITY,
+            f64::NAN,
+            0.,
+            -10.,
+            f64::NEG_INFINITY,
+            f64::MAX,
+        ] {
+            assert!(trajectory.resample(rate).is_empty(), "{rate}");
+        }
+        // the time does not advance by 1 / rate, the count still ends the loop
+        let single: Trajectory<f64> = [(1000., start())].into_iter().collect();
+        assert_eq!(single.resample(1e20).len(), 1);
+    }
+
+    #[test]
+    fn test_velocities() {
+        let mut trajectory = trajectory();
+        // a repeated timestamp is skipped
+        trajectory.push(0.5, trajectory.interpolate(0.5).unwrap());
+        let body = trajectory.body_velocities();
+        let space = trajectory.space_velocities();
+        assert_eq!(body.len(), 5);
+        assert_eq!(body[0].0, 0.05);
+        let twist_space = Vec6 {
+            val: start().adjoint().val * twist().val,
+        };
+        for ((_, b), (_, s)) in body.iter().zip(&space) {
+            assert_relative_eq!(b, &twist(), epsilon = 1e-12);
+            assert_relative_eq!(s, &twist_space, epsilon = 1e-12);
+        }
+        // the space velocity is the derivative of T(t) T(0)⁻¹
+        let dt = 1e-6;
+        let numeric = trajectory
+            .interpolate(0.2 + dt)
+            .unwrap()
+            .mat_mul(&trajectory.interpolate(0.2).unwrap().inv())
+            .log()
+            .vee()
+            * (1. / dt);
+        assert_relative_eq!(numeric, twist_space, epsilon = 1e-5);
+    }
+}
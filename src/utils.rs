@@ -1,35 +1,394 @@
-use nalgebra::{Matrix3, Matrix4, Vector3, Vector6};
+use nalgebra::{Matrix3, Matrix4, SMatrix, SVector, Vector3, Vector6};
 
-use crate::Real;
+use crate::{simd::select_with, Real, SimdReal};
 
-pub(crate) fn approx_zero<T: Real>(v: T) -> bool {
-    v < T::epsilon()
-}
-
-pub(crate) fn approx_zero_vec<T: Real>(v: &Vector3<T>) -> bool {
-    length(v) < T::epsilon()
-}
-
-pub(crate) fn axis_angle<T: Real>(v: &Vector3<T>) -> (Vector3<T>, T) {
-    let angle = length(v);
-    (v / angle, angle)
-}
-
-pub(crate) fn length<T: Real>(v: &Vector3<T>) -> T {
-    (v.x * v.x + v.y * v.y + v.z * v.z).sqrt()
+pub(crate) fn length_squared<T: SimdReal>(v: &Vector3<T>) -> T {
+    v.x * v.x + v.y * v.y + v.z * v.z
 }
 
 /// hat operator for vector3
-pub fn hat<T: Real>(v: &Vector3<T>) -> Matrix3<T> {
+pub fn hat<T: SimdReal>(v: &Vector3<T>) -> Matrix3<T> {
     let zero = T::zero();
     Matrix3::new(zero, -v[2], v[1], v[2], zero, -v[0], -v[1], v[0], zero)
 }
 
 /// hat operator for vector6
-pub fn hat_se3<T: Real>(v: &Vector6<T>) -> Matrix4<T> {
+pub fn hat_se3<T: SimdReal>(v: &Vector6<T>) -> Matrix4<T> {
     let zero = T::zero();
     Matrix4::new(
         zero, -v[2], v[1], v[3], v[2], zero, -v[0], v[4], -v[1], v[0], zero, v[5], zero, zero,
         zero, zero,
     )
 }
+
+/// rotation matrix from quaternion `[x, y, z, w]`, the quaternion is normalized first
+pub(crate) fn quat_to_rot<T: Real>(q: &[T; 4]) -> Matrix3<T> {
+    let norm = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    let (x, y, z, w) = (q[0] / norm, q[1] / norm, q[2] / norm, q[3] / norm);
+    let one = T::one();
+    let two = one + one;
+    Matrix3::new(
+        one - two * (y * y + z * z),
+        two * (x * y - z * w),
+        two * (x * z + y * w),
+        two * (x * y + z * w),
+        one - two * (x * x + z * z),
+        two * (y * z - x * w),
+        two * (x * z - y * w),
+        two * (y * z + x * w),
+        one - two * (x * x + y * y),
+    )
+}
+
+/// unit quaternion `[x, y, z, w]` from rotation matrix, with `w >= 0`
+pub(crate) fn rot_to_quat<T: Real>(r: &Matrix3<T>) -> [T; 4] {
+    let one = T::one();
+    let two = one + one;
+    let four = two + two;
+    let trace = r.trace();
+    let q = if trace > T::zero() {
+        let s = (trace + one).sqrt() * two;
+        [
+            (r[(2, 1)] - r[(1, 2)]) / s,
+            (r[(0, 2)] - r[(2, 0)]) / s,
+            (r[(1, 0)] - r[(0, 1)]) / s,
+            s / four,
+        ]
+    } else if r[(0, 0)] > r[(1, 1)] && r[(0, 0)] > r[(2, 2)] {
+        let s = (one + r[(0, 0)] - r[(1, 1)] - r[(2, 2)]).sqrt() * two;
+        [
+            s / four,
+            (r[(0, 1)] + r[(1, 0)]) / s,
+            (r[(0, 2)] + r[(2, 0)]) / s,
+            (r[(2, 1)] - r[(1, 2)]) / s,
+        ]
+    } else if r[(1, 1)] > r[(2, 2)] {
+        let s = (one + r[(1, 1)] - r[(0, 0)] - r[(2, 2)]).sqrt() * two;
+        [
+            (r[(0, 1)] + r[(1, 0)]) / s,
+            s / four,
+            (r[(1, 2)] + r[(2, 1)]) / s,
+            (r[(0, 2)] - r[(2, 0)]) / s,
+        ]
+    } else {
+        let s = (one + r[(2, 2)] - r[(0, 0)] - r[(1, 1)]).sqrt() * two;
+        [
+            (r[(0, 2)] + r[(2, 0)]) / s,
+            (r[(1, 2)] + r[(2, 1)]) / s,
+            s / four,
+            (r[(1, 0)] - r[(0, 1)]) / s,
+        ]
+    };
+    if q[3] < T::zero() {
+        [-q[0], -q[1], -q[2], -q[3]]
+    } else {
+        q
+    }
+}
+
+/// whether `1 - cos θ` is small enough to treat the rotation as the identity in Taylor expansions
+pub(crate) fn small_angle<T: SimdReal>(x: T) -> T::Mask {
+    x.simd_lt(T::simd_epsilon().simd_sqrt())
+}
+
+/// the coefficients below are evaluated with Taylor series in θ² for θ² < 1,
+/// where the closed forms lose precision by cancellation,
+/// the series are truncated where the next term is below f64 precision
+fn use_series<T: SimdReal>(theta2: T) -> T::Mask {
+    theta2.simd_lt(T::one())
+}
+
+/// evaluate `Σ coefs[k] x^k`
+fn series<T: SimdReal>(x: T, coefs: &[f64]) -> T {
+    coefs
+        .iter()
+        .rev()
+        .fold(T::zero(), |acc, &c| acc * x + T::splat(c))
+}
+
+/// `sin θ / θ`
+const SERIES_A: [f64; 9] = [
+    1.,
+    -1. / 6.,
+    1. / 120.,
+    -1. / 5040.,
+    1. / 362880.,
+    -1. / 39916800.,
+    1. / 6227020800.,
+    -1. / 1307674368000.,
+    1. / 355687428096000.,
+];
+
+/// `(1 - cos θ) / θ²`
+const SERIES_B: [f64; 9] = [
+    1. / 2.,
+    -1. / 24.,
+    1. / 720.,
+    -1. / 40320.,
+    1. / 3628800.,
+    -1. / 479001600.,
+    1. / 87178291200.,
+    -1. / 20922789888000.,
+    1. / 6402373705728000.,
+];
+
+/// `(θ - sin θ) / θ³`
+const SERIES_C: [f64; 9] = [
+    1. / 6.,
+    -1. / 120.,
+    1. / 5040.,
+    -1. / 362880.,
+    1. / 39916800.,
+    -1. / 6227020800.,
+    1. / 1307674368000.,
+    -1. / 355687428096000.,
+    1. / 121645100408832000.,
+];
+
+/// `1 / θ² - (1 + cos θ) / (2 θ sin θ)`, coefficients are `-B_2k / (2k)!`
+const SERIES_D: [f64; 11] = [
+    1. / 12.,
+    1. / 720.,
+    1. / 30240.,
+    1. / 1209600.,
+    1. / 47900160.,
+    691. / 1307674368000.,
+    1. / 74724249600.,
+    3617. / 10670622842880000.,
+    43867. / 5109094217170944000.,
+    174611. / 802857662698291200000.,
+    77683. / 14101100039391805440000.,
+];
+
+/// `(θ² + 2 cos θ - 2) / (2 θ⁴)`
+const SERIES_SE3_B: [f64; 9] = [
+    1. / 24.,
+    -1. / 720.,
+    1. / 40320.,
+    -1. / 3628800.,
+    1. / 479001600.,
+    -1. / 87178291200.,
+    1. / 20922789888000.,
+    -1. / 6402373705728000.,
+    1. / 2432902008176640000.,
+];
+
+/// `(2θ - 3 sin θ + θ cos θ) / (2 θ⁵)`
+const SERIES_SE3_C: [f64; 9] = [
+    1. / 120.,
+    -1. / 2520.,
+    1. / 120960.,
+    -1. / 9979200.,
+    1. / 1245404160.,
+    -1. / 217945728000.,
+    1. / 50812489728000.,
+    -1. / 15205637551104000.,
+    1. / 5676771352412160000.,
+];
+
+/// coefficients `sin θ / θ`, `(1 - cos θ) / θ²` and `(θ - sin θ) / θ³` of the SO3 exp map and Jacobians
+///
+/// computed from θ² so that the derivatives are exact at θ = 0
+pub(crate) fn so3_coefs<T: SimdReal>(theta2: T) -> (T, T, T) {
+    let [a, b, c] = select_with(
+        use_series(theta2),
+        || {
+            [
+                series(theta2, &SERIES_A),
+                series(theta2, &SERIES_B),
+                series(theta2, &SERIES_C),
+            ]
+        },
+        || {
+            let theta = theta2.simd_sqrt();
+            let (sin, cos) = theta.simd_sin_cos();
+            [
+                sin / theta,
+                (T::one() - cos) / theta2,
+                (theta - sin) / (theta2 * theta),
+            ]
+        },
+    );
+    (a, b, c)
+}
+
+/// coefficient `1 / θ² - (1 + cos θ) / (2 θ sin θ)` of the inverse SO3 Jacobians
+pub(crate) fn so3_jac_inv_coef<T: SimdReal>(theta2: T) -> T {
+    let [d] = select_with(
+        use_series(theta2),
+        || [series(theta2, &SERIES_D)],
+        || {
+            let two = T::one() + T::one();
+            let theta = theta2.simd_sqrt();
+            let (sin, cos) = (theta / two).simd_sin_cos();
+            [T::one() / theta2 - cos / (two * theta * sin)]
+        },
+    );
+    d
+}
+
+/// coefficients `(θ² + 2 cos θ - 2) / (2 θ⁴)` and `(2θ - 3 sin θ + θ cos θ) / (2 θ⁵)`
+/// of the SE3 Jacobians
+pub(crate) fn se3_jac_coefs<T: SimdReal>(theta2: T) -> (T, T) {
+    let [b, c] = select_with(
+        use_series(theta2),
+        || [series(theta2, &SERIES_SE3_B), series(theta2, &SERIES_SE3_C)],
+        || {
+            let c = T::splat;
+            let theta = theta2.simd_sqrt();
+            let (sin, cos) = theta.simd_sin_cos();
+            let theta4 = theta2 * theta2;
+            [
+                (theta2 + c(2.) * cos - c(2.)) / (c(2.) * theta4),
+                (c(2.) * theta - c(3.) * sin + theta * cos) / (c(2.) * theta4 * theta),
+            ]
+        },
+    );
+    (b, c)
+}
+
+/// write the Jacobian if it is requested
+pub(crate) fn set_jac<M>(jac: Option<&mut M>, value: impl FnOnce() -> M) {
+    if let Some(jac) = jac {
+        *jac = value();
+    }
+}
+
+/// `⟨A⟩ = -tr(A) I + A`, operator of the fourth order covariance propagation
+pub(crate) fn bracket<T: Real>(a: &Matrix3<T>) -> Matrix3<T> {
+    a - Matrix3::identity() * a.trace()
+}
+
+/// `⟨⟨A, B⟩⟩ = ⟨A⟩⟨B⟩ + ⟨BA⟩`
+pub(crate) fn double_bracket<T: Real>(a: &Matrix3<T>, b: &Matrix3<T>) -> Matrix3<T> {
+    bracket(a) * bracket(b) + bracket(&(b * a))
+}
+
+/// solve the symmetric positive definite system `H x = g` by LDLᵀ,
+/// `None` if `H` is singular relative to its largest pivot
+pub(crate) fn solve_spd<T: Real, const N: usize>(
+    h: &SMatrix<T, N, N>,
+    g: &SVector<T, N>,
+) -> Option<SVector<T, N>> {
+    let mut l = SMatrix::<T, N, N>::identity();
+    let mut d = SVector::<T, N>::zeros();
+    let scale = (0..N).fold(T::zero(), |m, i| m.max(h[(i, i)]));
+    let tol = scale * T::epsilon().sqrt();
+    for j in 0..N {
+        let mut dj = h[(j, j)];
+        for k in 0..j {
+            dj -= l[(j, k)] * l[(j, k)] * d[k];
+        }
+        if dj.partial_cmp(&tol) != Some(core::cmp::Ordering::Greater) {
+            return None;
+        }
+        d[j] = dj;
+        for i in j + 1..N {
+            let mut s = h[(i, j)];
+            for k in 0..j {
+                s -= l[(i, k)] * l[(j, k)] * d[k];
+            }
+            l[(i, j)] = s / dj;
+        }
+    }
+    // forward, diagonal and backward substitution
+    let mut x = *g;
+    for i in 0..N {
+        for k in 0..i {
+            let lx = l[(i, k)] * x[k];
+            x[i] -= lx;
+        }
+    }
+    for i in 0..N {
+        x[i] /= d[i];
+    }
+    for i in (0..N).rev() {
+        for k in i + 1..N {
+            let lx = l[(k, i)] * x[k];
+            x[i] -= lx;
+        }
+    }
+    Some(x)
+}
+
+/// cholesky decomposition `A = L Lᵀ` of a symmetric positive definite matrix, reading the lower triangle
+#[cfg(any(feature = "alloc", feature = "rand"))]
+pub(crate) fn cholesky<T: Real, const N: usize>(a: &SMatrix<T, N, N>) -> Option<SMatrix<T, N, N>> {
+    let mut l = SMatrix::<T, N, N>::zeros();
+    for j in 0..N {
+        let mut d = a[(j, j)];
+        for k in 0..j {
+            d -= l[(j, k)] * l[(j, k)];
+        }
+        if d.partial_cmp(&T::zero()) != Some(core::cmp::Ordering::Greater) {
+            return None;
+        }
+        let d = d.sqrt();
+        l[(j, j)] = d;
+        for i in j + 1..N {
+            let mut s = a[(i, j)];
+            for k in 0..j {
+                s -= l[(i, k)] * l[(j, k)];
+            }
+            l[(i, j)] = s / d;
+        }
+    }
+    Some(l)
+}
+
+/// error of optional per-element weights
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WeightError {
+    /// the number of weights differs from the number of elements
+    LengthMismatch,
+    /// a weight is negative or not finite, or all weights are zero
+    Invalid,
+}
+
+/// check optional weights of `len` elements and return their sum, `len` if no weight is given
+pub(crate) fn weight_sum<T: Real>(len: usize, weights: Option<&[T]>) -> Result<T, WeightError> {
+    let Some(weights) = weights else {
+        return Ok(T::from(len).unwrap());
+    };
+    if weights.len() != len {
+        return Err(WeightError::LengthMismatch);
+    }
+    let mut sum = T::zero();
+    for &w in weights {
+        if !(w >= T::zero() && w < T::max_value()) {
+            return Err(WeightError::Invalid);
+        }
+        sum += w;
+    }
+    if sum > T::zero() {
+        Ok(sum)
+    } else {
+        Err(WeightError::Invalid)
+    }
+}
+
+/// `i`-th weight, 1 if no weight is given
+pub(crate) fn weight<T: Real>(weights: Option<&[T]>, i: usize) -> T {
+    weights.map_or(T::one(), |w| w[i])
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nalgebra::Vector3;
+
+    /// angles from 1e-12 to π - 1e-9
+    pub(crate) fn sweep() -> impl Iterator<Item = f64> {
+        let small = (0..24).map(|i| 10f64.powf(-12. + i as f64 / 2.));
+        let large = (0..8).map(|i| 1. + 2. * i as f64 / 8.);
+        let pi = [1e-3, 1e-6, 1e-9].map(|d| core::f64::consts::PI - d);
+        small.chain(large).chain(pi)
+    }
+
+    pub(crate) fn axes() -> [Vector3<f64>; 3] {
+        [
+            Vector3::new(1., 0., 0.),
+            Vector3::new(1., 2., 3.).normalize(),
+            Vector3::new(-0.3, 0.1, 0.9).normalize(),
+        ]
+    }
+}
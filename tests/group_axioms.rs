@@ -0,0 +1,150 @@
+//! Property-based tests of the group axioms and exp/log round-trips of SO3 and SE3
+
+use core::f64::consts::PI;
+
+use approx::relative_eq;
+use liealg::{hat, hat_se3, prelude::*, Vec3, Vec6, SE3, SO3};
+use nalgebra::{Matrix3, Matrix4, Vector3, Vector6};
+use proptest::prelude::*;
+
+const EPS: f64 = 1e-9;
+
+/// rotation vector with angle in `[0, max_angle]`
+fn vec3(max_angle: f64) -> impl Strategy<Value = Vec3<f64>> {
+    (
+        prop::array::uniform3(-1.0..1.0f64).prop_filter("axis too short", |a| {
+            a[0] * a[0] + a[1] * a[1] + a[2] * a[2] > 1e-2
+        }),
+        0.0..max_angle,
+    )
+        .prop_map(|(axis, angle)| {
+            let axis = Vector3::from(axis).normalize() * angle;
+            Vec3::new(axis.x, axis.y, axis.z)
+        })
+}
+
+fn vec6(max_angle: f64) -> impl Strategy<Value = Vec6<f64>> {
+    (vec3(max_angle), prop::array::uniform3(-10.0..10.0f64))
+        .prop_map(|(w, p)| Vec6::new(w.as_array(), p))
+}
+
+/// rotation from euler angles, independent of the exp map
+fn rot() -> impl Strategy<Value = SO3<f64>> {
+    (-PI..PI, -PI / 2.0..PI / 2.0, -PI..PI)
+        .prop_map(|(roll, pitch, yaw)| SO3::from_euler_angles(roll, pitch, yaw))
+}
+
+fn pose() -> impl Strategy<Value = SE3<f64>> {
+    (rot(), prop::array::uniform3(-10.0..10.0f64)).prop_map(|(r, p)| SE3::new(&r, p))
+}
+
+fn mat3(rot: &SO3<f64>) -> Matrix3<f64> {
+    Matrix3::from_column_slice(&rot.as_array())
+}
+
+fn vector3(v: &Vec3<f64>) -> Vector3<f64> {
+    Vector3::from(v.as_array())
+}
+
+fn vector6(v: &Vec6<f64>) -> Vector6<f64> {
+    Vector6::from(v.as_array())
+}
+
+proptest! {
+    #[test]
+    fn so3_exp_log(v in vec3(PI - 1e-6)) {
+        let v_ = v.hat().exp().log().vee();
+        prop_assert!(relative_eq!(v_, v, epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_log_exp(r in rot()) {
+        prop_assert!(relative_eq!(r.log().exp(), r, epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_log_exp_near_pi(v in vec3(1e-3)) {
+        // rotation by `π - |v|`, exercising the symmetric branch of log
+        let angle = PI - vector3(&v).norm();
+        let axis = vector3(&v).try_normalize(0.).unwrap_or(Vector3::x()) * angle;
+        let r = Vec3::new(axis.x, axis.y, axis.z).hat().exp();
+        prop_assert!(relative_eq!(r.log().exp(), r, epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_associativity(a in rot(), b in rot(), c in rot()) {
+        let ab_c = a.mat_mul(&b).mat_mul(&c);
+        let a_bc = a.mat_mul(&b.mat_mul(&c));
+        prop_assert!(relative_eq!(ab_c, a_bc, epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_inverse(r in rot()) {
+        prop_assert!(relative_eq!(r.mat_mul(&r.inv()), SO3::identity(), epsilon = EPS));
+        prop_assert!(relative_eq!(r.inv().mat_mul(&r), SO3::identity(), epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_adjoint(r in rot(), v in vec3(PI)) {
+        let lhs = hat(&vector3(&r.adjoint().act(&v.hat()).vee()));
+        let rhs = mat3(&r) * hat(&vector3(&v)) * mat3(&r).transpose();
+        prop_assert!(relative_eq!(lhs, rhs, epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_adjoint_homomorphism(a in rot(), b in rot(), v in vec3(PI)) {
+        let lhs = a.mat_mul(&b).adjoint().act(&v.hat());
+        let rhs = a.adjoint().act(&b.adjoint().act(&v.hat()));
+        prop_assert!(relative_eq!(lhs, rhs, epsilon = EPS));
+    }
+
+    #[test]
+    fn so3_plus_minus(a in rot(), b in rot()) {
+        prop_assert!(relative_eq!(a.plus(&b.minus(&a)), b, epsilon = EPS));
+    }
+
+    #[test]
+    fn se3_exp_log(v in vec6(PI - 1e-6)) {
+        let v_ = v.hat().exp().log().vee();
+        prop_assert!(relative_eq!(v_, v, epsilon = 1e-8));
+    }
+
+    #[test]
+    fn se3_log_exp(t in pose()) {
+        prop_assert!(relative_eq!(t.log().exp(), t, epsilon = EPS));
+    }
+
+    #[test]
+    fn se3_associativity(a in pose(), b in pose(), c in pose()) {
+        let ab_c = a.mat_mul(&b).mat_mul(&c);
+        let a_bc = a.mat_mul(&b.mat_mul(&c));
+        prop_assert!(relative_eq!(ab_c, a_bc, epsilon = EPS));
+    }
+
+    #[test]
+    fn se3_inverse(t in pose()) {
+        prop_assert!(relative_eq!(t.mat_mul(&t.inv()), SE3::identity(), epsilon = EPS));
+        prop_assert!(relative_eq!(t.inv().mat_mul(&t), SE3::identity(), epsilon = EPS));
+    }
+
+    #[test]
+    fn se3_adjoint(t in pose(), v in vec6(PI)) {
+        let lhs = hat_se3(&vector6(&t.adjoint().act(&v.hat()).vee()));
+        let m: Matrix4<f64> = t.matrix();
+        let m_inv: Matrix4<f64> = t.inv().matrix();
+        let rhs = m * hat_se3(&vector6(&v)) * m_inv;
+        prop_assert!(relative_eq!(lhs, rhs, epsilon = EPS));
+    }
+
+    #[test]
+    fn se3_adjoint_homomorphism(a in pose(), b in pose(), v in vec6(PI)) {
+        let lhs = a.mat_mul(&b).adjoint().act(&v.hat());
+        let rhs = a.adjoint().act(&b.adjoint().act(&v.hat()));
+        prop_assert!(relative_eq!(lhs, rhs, epsilon = EPS));
+    }
+
+    #[test]
+    fn se3_plus_minus(a in pose(), b in pose()) {
+        prop_assert!(relative_eq!(a.plus(&b.minus(&a)), b, epsilon = EPS));
+    }
+}